@@ -0,0 +1,398 @@
+//! Builder of [`DlsiteClient`]. For more information, see [`DlsiteClientBuilder`].
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, COOKIE, USER_AGENT};
+
+use super::{
+    cache::CacheConfig,
+    rate_limit::{RateLimitConfig, RateLimiter},
+    retry::RetryPolicy,
+    split_base_url,
+    transport::{ReqwestTransport, Transport},
+    unknown_field::UnknownFieldPolicy,
+    ClientInner, DlsiteClient, ParseMode,
+};
+use crate::{
+    error::Result,
+    interface::{locale::Locale, site::Site},
+    DlsiteError,
+};
+
+/// Builder to configure a [`DlsiteClient`]. Created by [`DlsiteClient::builder`].
+///
+/// Headers, user agent, cookies and locale are applied to every request regardless of the
+/// transport. Timeouts and proxy are settings of the underlying reqwest client, so they are
+/// ignored when a pre-built client or a custom transport is given.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use dlsite::{interface::locale::Locale, DlsiteClient};
+///
+/// let client = DlsiteClient::builder()
+///     .timeout(Duration::from_secs(30))
+///     .user_agent("my-crawler/1.0")
+///     .cookie("adultchecked", "1")
+///     .locale(Locale::English)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct DlsiteClientBuilder {
+    base_url: String,
+    site: Option<Site>,
+    locale: Locale,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    proxy: Option<reqwest::Proxy>,
+    headers: HeaderMap,
+    cookies: Vec<(String, String)>,
+    reqwest_client: Option<reqwest::Client>,
+    transport: Option<Arc<dyn Transport>>,
+    retry_policy: RetryPolicy,
+    rate_limit: Option<RateLimitConfig>,
+    cache: Option<CacheConfig>,
+    dump_dir: Option<PathBuf>,
+    parse_mode: ParseMode,
+    unknown_field_policy: UnknownFieldPolicy,
+}
+
+impl Default for DlsiteClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: "https://www.dlsite.com/maniax".to_string(),
+            site: None,
+            locale: Locale::default(),
+            timeout: None,
+            connect_timeout: None,
+            user_agent: None,
+            proxy: None,
+            headers: HeaderMap::new(),
+            cookies: vec![],
+            reqwest_client: None,
+            transport: None,
+            retry_policy: RetryPolicy::none(),
+            rate_limit: None,
+            cache: None,
+            dump_dir: None,
+            parse_mode: ParseMode::default(),
+            unknown_field_policy: UnknownFieldPolicy::default(),
+        }
+    }
+}
+
+impl DlsiteClientBuilder {
+    /// Set the base URL. Default is `https://www.dlsite.com/maniax`.
+    ///
+    /// For how the site at the end of the base URL is used, see [`DlsiteClient::new`].
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.to_string();
+        self
+    }
+
+    /// Set the default site, replacing the site of the base URL.
+    pub fn site(mut self, site: Site) -> Self {
+        self.site = Some(site);
+        self
+    }
+
+    /// Set the locale of pages and APIs. Default is [`Locale::Japanese`].
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Set the total timeout of each request. Can't be used with [`Self::reqwest_client`] or
+    /// [`Self::transport`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout for connecting to the server. Can't be used with [`Self::reqwest_client`]
+    /// or [`Self::transport`].
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the `User-Agent` header.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Send all requests through the proxy. Can't be used with [`Self::reqwest_client`] or
+    /// [`Self::transport`].
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Add a header sent with every request.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Add a cookie sent with every request.
+    pub fn cookie(mut self, name: &str, value: &str) -> Self {
+        self.cookies.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Use a pre-built reqwest client instead of building one. Configure timeouts and proxy on the
+    /// client.
+    pub fn reqwest_client(mut self, client: reqwest::Client) -> Self {
+        self.reqwest_client = Some(client);
+        self
+    }
+
+    /// Use a custom transport instead of reqwest. Can't be used with [`Self::reqwest_client`].
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Set the policy to retry transient failures. Default is [`RetryPolicy::none`].
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Limit the rate of requests. No limit by default.
+    pub fn rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limit = Some(config);
+        self
+    }
+
+    /// Cache responses. No cache by default.
+    pub fn cache(mut self, config: CacheConfig) -> Self {
+        self.cache = Some(config);
+        self
+    }
+
+    /// Save response bodies which failed to be parsed into `dir`, to attach them to bug reports.
+    ///
+    /// The path of the saved file is set to [`ParseError::dump_path`](crate::error::ParseError::dump_path).
+    pub fn dump_parse_failures(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dump_dir = Some(dir.into());
+        self
+    }
+
+    /// Set how strictly responses are parsed. Default is [`ParseMode::Lenient`].
+    pub fn parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// Set what to do with unknown fields in JSON responses. Default is
+    /// [`UnknownFieldPolicy::Ignore`].
+    pub fn unknown_field_policy(mut self, policy: UnknownFieldPolicy) -> Self {
+        self.unknown_field_policy = policy;
+        self
+    }
+
+    /// Build the client.
+    ///
+    /// Returns [`DlsiteError::Config`] if settings conflict, e.g. [`Self::timeout`] with
+    /// [`Self::transport`], instead of ignoring some of them.
+    pub fn build(self) -> Result<DlsiteClient> {
+        let custom = match (&self.transport, &self.reqwest_client) {
+            (Some(_), Some(_)) => {
+                return Err(DlsiteError::Config(
+                    "transport and reqwest_client can't be set at once".to_string(),
+                ))
+            }
+            (Some(_), None) => Some("transport"),
+            (None, Some(_)) => Some("reqwest_client"),
+            (None, None) => None,
+        };
+        if let Some(custom) = custom {
+            let ignored = [
+                ("timeout", self.timeout.is_some()),
+                ("connect_timeout", self.connect_timeout.is_some()),
+                ("proxy", self.proxy.is_some()),
+            ];
+            if let Some((name, _)) = ignored.iter().find(|(_, set)| *set) {
+                return Err(DlsiteError::Config(format!(
+                    "{name} can't be set with {custom}; configure it on the {custom} instead"
+                )));
+            }
+        }
+
+        let mut headers = self.headers;
+        if let Some(user_agent) = self.user_agent {
+            headers.insert(
+                USER_AGENT,
+                HeaderValue::from_str(&user_agent)
+                    .map_err(|e| DlsiteError::Config(format!("Invalid user agent: {e}")))?,
+            );
+        }
+        if !self.cookies.is_empty() {
+            let cookie = self
+                .cookies
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<_>>()
+                .join("; ");
+            headers.insert(
+                COOKIE,
+                HeaderValue::from_str(&cookie)
+                    .map_err(|e| DlsiteError::Config(format!("Invalid cookie: {e}")))?,
+            );
+        }
+
+        let transport: Arc<dyn Transport> = match (self.transport, self.reqwest_client) {
+            (Some(transport), _) => transport,
+            (None, Some(client)) => Arc::new(ReqwestTransport::new(client)),
+            (None, None) => {
+                let mut builder = reqwest::Client::builder();
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(proxy) = self.proxy {
+                    builder = builder.proxy(proxy);
+                }
+                Arc::new(ReqwestTransport::new(builder.build()?))
+            }
+        };
+
+        let (origin, default_site) = split_base_url(&self.base_url);
+        Ok(DlsiteClient {
+            inner: Arc::new(ClientInner {
+                transport,
+                origin,
+                default_site: self.site.or(default_site),
+                headers,
+                locale: self.locale,
+                retry_policy: self.retry_policy,
+                rate_limiter: self.rate_limit.map(RateLimiter::new),
+                cache: self.cache,
+                dump_dir: self.dump_dir,
+                parse_mode: self.parse_mode,
+                unknown_field_policy: self.unknown_field_policy,
+                unknown_field_reports: Mutex::default(),
+            }),
+            use_cache: true,
+            site: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use reqwest::header::{HeaderMap, COOKIE, USER_AGENT};
+
+    use crate::{
+        client::transport::{BoxFuture, Request, Response, StatusCode, Transport},
+        error::Result,
+        DlsiteClient, DlsiteError,
+    };
+
+    #[derive(Debug, Default)]
+    struct HeaderTransport {
+        headers: Arc<Mutex<Option<HeaderMap>>>,
+    }
+
+    impl Transport for HeaderTransport {
+        fn send(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+            *self.headers.lock().unwrap() = Some(request.headers);
+            Box::pin(async {
+                Ok(Response {
+                    status: StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: String::new(),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn builder_headers() {
+        let transport = HeaderTransport::default();
+        let headers = transport.headers.clone();
+        let client = DlsiteClient::builder()
+            .user_agent("test-agent")
+            .cookie("adultchecked", "1")
+            .cookie("locale", "ja-jp")
+            .transport(transport)
+            .build()
+            .unwrap();
+        client.get("/").await.unwrap();
+
+        let headers = headers.lock().unwrap().take().unwrap();
+        assert_eq!(headers[USER_AGENT], "test-agent");
+        assert_eq!(headers[COOKIE], "adultchecked=1; locale=ja-jp");
+    }
+
+    #[tokio::test]
+    async fn builder_timeout() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            // Accept a connection and never respond.
+            let (_socket, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        });
+
+        let client = DlsiteClient::builder()
+            .base_url(&format!("http://{addr}"))
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let err = client.get("/").await.unwrap_err();
+        assert!(matches!(err, DlsiteError::Reqwest(e) if e.is_timeout()));
+    }
+
+    #[test]
+    fn builder_conflicts() {
+        let build = |builder: crate::client::builder::DlsiteClientBuilder| match builder.build() {
+            Err(DlsiteError::Config(msg)) => msg,
+            _ => panic!("Expected config error"),
+        };
+        assert!(build(
+            DlsiteClient::builder()
+                .transport(HeaderTransport::default())
+                .timeout(Duration::from_secs(1))
+        )
+        .starts_with("timeout"));
+        assert!(build(
+            DlsiteClient::builder()
+                .reqwest_client(reqwest::Client::new())
+                .connect_timeout(Duration::from_secs(1))
+        )
+        .starts_with("connect_timeout"));
+        assert!(build(
+            DlsiteClient::builder()
+                .reqwest_client(reqwest::Client::new())
+                .proxy(reqwest::Proxy::all("http://127.0.0.1:8080").unwrap())
+        )
+        .starts_with("proxy"));
+        build(
+            DlsiteClient::builder()
+                .reqwest_client(reqwest::Client::new())
+                .transport(HeaderTransport::default()),
+        );
+
+        DlsiteClient::builder()
+            .timeout(Duration::from_secs(1))
+            .proxy(reqwest::Proxy::all("http://127.0.0.1:8080").unwrap())
+            .build()
+            .unwrap();
+    }
+}
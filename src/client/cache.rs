@@ -0,0 +1,280 @@
+//! Response cache. For more information, see [`CacheConfig`].
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use super::Endpoint;
+use crate::utils::fnv1a;
+
+/// Storage of cached response bodies.
+///
+/// Keys are made from the request URL and the locale by [`DlsiteClient`](super::DlsiteClient).
+pub trait Cache: Debug + Send + Sync {
+    /// Get a value which is not expired.
+    fn get(&self, key: &str) -> Option<String>;
+    /// Store a value which expires after `ttl`.
+    fn put(&self, key: &str, value: &str, ttl: Duration);
+}
+
+/// Configuration of the response cache of [`DlsiteClient`](super::DlsiteClient).
+///
+/// Only responses of endpoints which have a TTL are cached. To bypass the cache for some calls,
+/// use [`DlsiteClient::without_cache`](super::DlsiteClient::without_cache).
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use dlsite::{
+///     client::{cache::{CacheConfig, MemoryCache}, Endpoint},
+///     DlsiteClient,
+/// };
+///
+/// let client = DlsiteClient::builder()
+///     .cache(
+///         CacheConfig::new(MemoryCache::new(1000))
+///             .ttl(Endpoint::Html, Duration::from_secs(24 * 60 * 60))
+///             .ttl(Endpoint::Ajax, Duration::from_secs(60 * 60)),
+///     )
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub(crate) cache: Arc<dyn Cache>,
+    default_ttl: Option<Duration>,
+    ttls: HashMap<Endpoint, Duration>,
+}
+
+impl CacheConfig {
+    /// Use `cache` as storage. Nothing is cached until TTLs are set.
+    pub fn new(cache: impl Cache + 'static) -> Self {
+        Self {
+            cache: Arc::new(cache),
+            default_ttl: None,
+            ttls: HashMap::new(),
+        }
+    }
+
+    /// Cache responses of all endpoints without their own TTL for `ttl`.
+    pub fn default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = Some(ttl);
+        self
+    }
+
+    /// Cache responses of `endpoint` for `ttl`.
+    pub fn ttl(mut self, endpoint: Endpoint, ttl: Duration) -> Self {
+        self.ttls.insert(endpoint, ttl);
+        self
+    }
+
+    pub(crate) fn ttl_for(&self, endpoint: Endpoint) -> Option<Duration> {
+        self.ttls.get(&endpoint).copied().or(self.default_ttl)
+    }
+}
+
+/// In-memory LRU cache.
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    inner: Mutex<MemoryCacheInner>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryCacheInner {
+    tick: u64,
+    entries: HashMap<String, MemoryCacheEntry>,
+}
+
+#[derive(Debug)]
+struct MemoryCacheEntry {
+    value: String,
+    expires_at: Instant,
+    used_at: u64,
+}
+
+impl MemoryCache {
+    /// Create a cache which holds up to `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(MemoryCacheInner::default()),
+        }
+    }
+}
+
+impl Cache for MemoryCache {
+    fn get(&self, key: &str) -> Option<String> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+        let entry = inner.entries.get_mut(key)?;
+        if entry.expires_at <= Instant::now() {
+            inner.entries.remove(key);
+            return None;
+        }
+        entry.used_at = tick;
+        Some(entry.value.clone())
+    }
+
+    fn put(&self, key: &str, value: &str, ttl: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+        inner.entries.insert(
+            key.to_string(),
+            MemoryCacheEntry {
+                value: value.to_string(),
+                expires_at: Instant::now() + ttl,
+                used_at: tick,
+            },
+        );
+        if inner.entries.len() > self.capacity {
+            let lru = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.used_at)
+                .map(|(k, _)| k.clone());
+            if let Some(lru) = lru {
+                inner.entries.remove(&lru);
+            }
+        }
+    }
+}
+
+/// Cache which stores each entry as a file in a directory, so it survives restarts.
+#[derive(Debug, Clone)]
+pub struct FileCache {
+    dir: PathBuf,
+}
+
+impl FileCache {
+    /// Create a cache in `dir`. The directory is created if it does not exist.
+    pub fn new(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.cache", fnv1a(key)))
+    }
+}
+
+/// File format: expiry (unix seconds), key and value separated by newlines.
+impl Cache for FileCache {
+    fn get(&self, key: &str) -> Option<String> {
+        let path = self.path(key);
+        let content = std::fs::read_to_string(&path).ok()?;
+        let (expires_at, rest) = content.split_once('\n')?;
+        let (stored_key, value) = rest.split_once('\n')?;
+        if stored_key != key {
+            return None;
+        }
+        if expires_at.parse::<u64>().ok()? <= unix_now() {
+            let _ = std::fs::remove_file(&path);
+            return None;
+        }
+        Some(value.to_string())
+    }
+
+    fn put(&self, key: &str, value: &str, ttl: Duration) {
+        let path = self.path(key);
+        let tmp = path.with_extension("tmp");
+        let content = format!("{}\n{}\n{}", unix_now() + ttl.as_secs(), key, value);
+        let result = std::fs::write(&tmp, content).and_then(|_| std::fs::rename(&tmp, &path));
+        if let Err(e) = result {
+            tracing::warn!("Failed to write cache {}: {e}", path.display());
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Cache, CacheConfig, FileCache, MemoryCache};
+    use crate::{
+        client::{
+            stub::{StubResponse, StubServer},
+            Endpoint,
+        },
+        DlsiteClient,
+    };
+
+    #[test]
+    fn memory_cache_lru() {
+        let cache = MemoryCache::new(2);
+        let ttl = Duration::from_secs(60);
+        cache.put("a", "1", ttl);
+        cache.put("b", "2", ttl);
+        assert_eq!(cache.get("a").as_deref(), Some("1"));
+        cache.put("c", "3", ttl);
+
+        assert_eq!(cache.get("a").as_deref(), Some("1"));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("c").as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn memory_cache_expire() {
+        let cache = MemoryCache::new(2);
+        cache.put("a", "1", Duration::ZERO);
+        assert_eq!(cache.get("a"), None);
+    }
+
+    #[test]
+    fn file_cache() {
+        let dir = std::env::temp_dir().join(format!("dlsite-cache-test-{}", std::process::id()));
+        let cache = FileCache::new(&dir).unwrap();
+        cache.put("a", "line1\nline2", Duration::from_secs(60));
+        cache.put("b", "2", Duration::ZERO);
+
+        let cache = FileCache::new(&dir).unwrap();
+        assert_eq!(cache.get("a").as_deref(), Some("line1\nline2"));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("c"), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn client_cache() {
+        let server = StubServer::start(vec![
+            StubResponse::ok("ajax"),
+            StubResponse::ok("html 1"),
+            StubResponse::ok("html 2"),
+            StubResponse::ok("ajax 2"),
+        ])
+        .await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .cache(
+                CacheConfig::new(MemoryCache::new(10)).ttl(Endpoint::Ajax, Duration::from_secs(60)),
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get("/ajax").await.unwrap(), "ajax");
+        assert_eq!(client.get("/html").await.unwrap(), "html 1");
+        assert_eq!(client.get("/ajax").await.unwrap(), "ajax");
+        assert_eq!(client.get("/html").await.unwrap(), "html 2");
+        assert_eq!(server.hits(), 3);
+
+        assert_eq!(client.without_cache().get("/ajax").await.unwrap(), "ajax 2");
+        assert_eq!(server.hits(), 4);
+    }
+}
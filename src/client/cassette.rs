@@ -0,0 +1,277 @@
+//! Record/replay transport for offline tests. For more information, see [`CassetteTransport`].
+
+use std::{path::PathBuf, sync::Arc};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+
+use super::transport::{BoxFuture, Method, Request, Response, StatusCode, Transport};
+use crate::{error::Result, utils::fnv1a, DlsiteError};
+
+/// [`Transport`] which records responses to a cassette directory, or replays them from it.
+///
+/// Each request is stored as a JSON file named after its method and URL. In replay mode, no
+/// network access is made and a request without a recorded response fails.
+///
+/// # Example
+/// ```no_run
+/// use dlsite::{
+///     client::{cassette::CassetteTransport, transport::ReqwestTransport},
+///     DlsiteClient,
+/// };
+///
+/// # async fn run() {
+/// // Record responses from DLsite.
+/// let client = DlsiteClient::builder()
+///     .transport(CassetteTransport::record("cassettes/rj01014447", ReqwestTransport::default()))
+///     .build()
+///     .unwrap();
+/// client.product_api().get(&"RJ01014447".parse().unwrap()).await.unwrap();
+///
+/// // Replay them without network access.
+/// let client = DlsiteClient::builder()
+///     .transport(CassetteTransport::replay("cassettes/rj01014447"))
+///     .build()
+///     .unwrap();
+/// client.product_api().get(&"RJ01014447".parse().unwrap()).await.unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CassetteTransport {
+    dir: PathBuf,
+    inner: Option<Arc<dyn Transport>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cassette {
+    request: CassetteRequest,
+    response: CassetteResponse,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CassetteRequest {
+    method: String,
+    url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CassetteResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl CassetteTransport {
+    /// Send requests through `inner` and record responses to `dir`.
+    pub fn record(dir: impl Into<PathBuf>, inner: impl Transport + 'static) -> Self {
+        Self {
+            dir: dir.into(),
+            inner: Some(Arc::new(inner)),
+        }
+    }
+
+    /// Replay responses recorded in `dir` without network access.
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            inner: None,
+        }
+    }
+
+    fn path(&self, method: &Method, url: &str) -> PathBuf {
+        let key = format!("{method} {url}");
+        // Readable prefix of the URL, followed by a hash to make the name unique.
+        let readable: String = url
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .take(80)
+            .collect();
+        self.dir.join(format!(
+            "{}-{readable}-{:016x}.json",
+            method.as_str(),
+            fnv1a(&key)
+        ))
+    }
+
+    async fn record_response(&self, inner: &dyn Transport, request: Request) -> Result<Response> {
+        let path = self.path(&request.method, &request.url);
+        let cassette_request = CassetteRequest {
+            method: request.method.to_string(),
+            url: request.url.clone(),
+        };
+        let res = inner.send(request).await?;
+
+        let cassette = Cassette {
+            request: cassette_request,
+            response: CassetteResponse {
+                status: res.status.as_u16(),
+                headers: res
+                    .headers
+                    .iter()
+                    .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+                    .collect(),
+                body: res.body.clone(),
+            },
+        };
+        std::fs::create_dir_all(&self.dir)
+            .and_then(|_| {
+                std::fs::write(&path, serde_json::to_string_pretty(&cassette)?)?;
+                Ok(())
+            })
+            .map_err(|e| DlsiteError::Transport(e.into()))?;
+
+        Ok(res)
+    }
+
+    fn replay_response(&self, request: &Request) -> Result<Response> {
+        let path = self.path(&request.method, &request.url);
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            DlsiteError::Transport(
+                format!(
+                    "No cassette for {} {} ({}): {e}",
+                    request.method,
+                    request.url,
+                    path.display()
+                )
+                .into(),
+            )
+        })?;
+        let cassette: Cassette = serde_json::from_str(&content)?;
+
+        let mut headers = HeaderMap::new();
+        for (k, v) in cassette.response.headers {
+            if let (Ok(k), Ok(v)) = (HeaderName::try_from(k), HeaderValue::try_from(v)) {
+                headers.append(k, v);
+            }
+        }
+        Ok(Response {
+            status: StatusCode::from_u16(cassette.response.status)
+                .map_err(|e| DlsiteError::Transport(e.into()))?,
+            headers,
+            body: cassette.response.body,
+        })
+    }
+}
+
+impl Transport for CassetteTransport {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+        Box::pin(async move {
+            match &self.inner {
+                Some(inner) => self.record_response(inner.as_ref(), request).await,
+                None => self.replay_response(&request),
+            }
+        })
+    }
+}
+
+/// Client for tests which hit DLsite.
+///
+/// Mode is selected by `DLSITE_CASSETTE` environment variable:
+/// * `record`: Access DLsite and record responses to `tests/cassettes/{name}`.
+/// * `live`: Access DLsite without recording.
+/// * Otherwise: Replay `tests/cassettes/{name}`. Panics if it doesn't exist, so that tests never
+///   access DLsite by accident.
+#[cfg(test)]
+pub(crate) fn test_client(name: &str) -> super::DlsiteClient {
+    test_client_builder(name).build().unwrap()
+}
+
+/// Builder of [`test_client`], to configure the client further.
+#[cfg(test)]
+pub(crate) fn test_client_builder(name: &str) -> super::builder::DlsiteClientBuilder {
+    use super::transport::ReqwestTransport;
+
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/cassettes")
+        .join(name);
+    let builder = super::DlsiteClient::builder();
+    let builder = match std::env::var("DLSITE_CASSETTE").as_deref() {
+        Ok("record") => {
+            builder.transport(CassetteTransport::record(dir, ReqwestTransport::default()))
+        }
+        Ok("live") => builder,
+        _ if dir.exists() => builder.transport(CassetteTransport::replay(dir)),
+        _ => panic!(
+            "No cassette at {}. Record it with `DLSITE_CASSETTE=record cargo test`.",
+            dir.display()
+        ),
+    };
+    builder
+}
+
+/// Body of the response recorded in `tests/cassettes/{name}` for the URL containing `url_part`.
+/// Panics if it is not recorded, like [`test_client`].
+#[cfg(test)]
+pub(crate) fn recorded_body(name: &str, url_part: &str) -> String {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/cassettes")
+        .join(name);
+    let entries = std::fs::read_dir(&dir).unwrap_or_else(|e| {
+        panic!(
+            "No cassette at {} ({e}). Record it with `DLSITE_CASSETTE=record cargo test`.",
+            dir.display()
+        )
+    });
+    for entry in entries {
+        let content = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        let cassette: Cassette = serde_json::from_str(&content).unwrap();
+        if cassette.request.url.contains(url_part) {
+            return cassette.response.body;
+        }
+    }
+    panic!("No response for {url_part} in {}", dir.display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CassetteTransport;
+    use crate::{
+        client::{
+            stub::{StubResponse, StubServer},
+            transport::ReqwestTransport,
+        },
+        DlsiteClient, DlsiteError,
+    };
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("dlsite-cassette-test-{}", std::process::id()));
+        let server = StubServer::start(vec![
+            StubResponse::ok("[]").header("Content-Type", "application/json"),
+            StubResponse::status(404),
+        ])
+        .await;
+
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .transport(CassetteTransport::record(&dir, ReqwestTransport::default()))
+            .build()
+            .unwrap();
+        assert_eq!(client.get("/a?x=1").await.unwrap(), "[]");
+        assert!(matches!(
+            client.get("/b").await,
+            Err(DlsiteError::HttpStatus { code: 404, .. })
+        ));
+        assert_eq!(server.hits(), 2);
+
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .transport(CassetteTransport::replay(&dir))
+            .build()
+            .unwrap();
+        assert_eq!(client.get("/a?x=1").await.unwrap(), "[]");
+        assert!(matches!(
+            client.get("/b").await,
+            Err(DlsiteError::HttpStatus { code: 404, .. })
+        ));
+        assert!(matches!(
+            client.get("/c").await,
+            Err(DlsiteError::Transport(_))
+        ));
+        assert_eq!(server.hits(), 2);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,45 +1,70 @@
 //! Interfaces related to circle only. For more information, see [`CircleClient`].
 
+mod profile;
 mod query;
+mod sale;
+
+use std::collections::HashSet;
 
 use scraper::{Html, Selector};
 
 use super::{
-    search::{parse_search_html, SearchResult},
+    paginate::{paginate, Page, PageStream, StreamOptions},
+    search::{parse_search_html, SearchProductItem, SearchResult},
     DlsiteClient,
 };
-use crate::{error::Result, utils::ToParseError as _};
+use crate::{error::Result, interface::id::MakerId, utils::ToParseError as _};
 
-pub use self::query::CircleQuery;
+pub(crate) use self::{profile::parse_circle_profile, sale::parse_circle_sales};
+pub use self::{profile::CircleProfile, query::CircleQuery, sale::CircleSaleItem};
 
 /// Client to get circle-related content from DLsite.
 #[derive(Clone, Debug)]
-pub struct CircleClient<'a> {
-    pub(crate) c: &'a DlsiteClient,
+pub struct CircleClient {
+    pub(crate) c: DlsiteClient,
 }
 
-impl<'a> CircleClient<'a> {
+impl CircleClient {
+    /// Get the profile of a circle, such as name, links and the number of works.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlsite::DlsiteClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = DlsiteClient::default();
+    ///     let profile = client.circle().get_profile(&"RG24350".parse().unwrap()).await.unwrap();
+    ///     println!("{} ({} works)", profile.name, profile.work_count);
+    /// }
+    /// ```
+    pub async fn get_profile(&self, circle_id: &MakerId) -> Result<CircleProfile> {
+        let c = self.c.for_id_site(circle_id.site());
+        let path = format!("/circle/profile/=/maker_id/{circle_id}.html");
+        let html = c
+            .get(&path)
+            .await
+            .map_err(|e| e.with_not_found_id(circle_id.as_str()))?;
+        let parsed = parse_circle_profile(&html);
+
+        c.parse_context(parsed, &path, Some(circle_id.as_str()), &html)
+    }
+
     /// Search circle-related products.
-    pub async fn get_circle(&self, circle_id: &str, options: &CircleQuery) -> Result<SearchResult> {
-        let query_path = options.to_path(circle_id);
-        let html = self.c.get(&query_path).await?;
-        let html = Html::parse_fragment(&html);
-        let products_html = html
-            .select(&Selector::parse("#search_result_list").unwrap())
-            .next()
-            .to_parse_error("Product list not found")?;
-
-        let count: i32 = html
-            .select(&Selector::parse(".page_total > strong").unwrap())
-            .next()
-            .to_parse_error("No total item count found")?
-            .text()
-            .next()
-            .to_parse_error("No total item count found 2")?
-            .parse()
-            .to_parse_error("Failed to parse total item count")?;
-
-        let products = parse_search_html(&products_html.html())?;
+    pub async fn get_circle(
+        &self,
+        circle_id: &MakerId,
+        options: &CircleQuery,
+    ) -> Result<SearchResult> {
+        let c = self.c.for_id_site(circle_id.site());
+        let query_path = options.to_path(circle_id.as_str());
+        let html = c
+            .get(&query_path)
+            .await
+            .map_err(|e| e.with_not_found_id(circle_id.as_str()))?;
+        let parsed = parse_circle_html(&html);
+        let (products, count) =
+            c.parse_context(parsed, &query_path, Some(circle_id.as_str()), &html)?;
 
         Ok(SearchResult {
             products,
@@ -47,19 +72,263 @@ impl<'a> CircleClient<'a> {
             query_path,
         })
     }
+
+    /// Get works of a circle which are on discount or point-up campaign. Works on each campaign
+    /// are requested with [`CircleQuery::campaign`] and [`CircleQuery::is_pointup`], and all
+    /// their pages are fetched. `page`, `campaign` and `is_pointup` of `options` are ignored.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlsite::{client::circle::CircleQuery, DlsiteClient};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = DlsiteClient::default();
+    ///     let sales = client
+    ///         .circle()
+    ///         .get_sales(&"RG24350".parse().unwrap(), &CircleQuery::default())
+    ///         .await
+    ///         .unwrap();
+    ///     for sale in sales {
+    ///         println!("{}: {:?}% off until {:?}", sale.product.title, sale.discount_rate, sale.campaign_end);
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_sales(
+        &self,
+        circle_id: &MakerId,
+        options: &CircleQuery,
+    ) -> Result<Vec<CircleSaleItem>> {
+        let c = self.c.for_id_site(circle_id.site());
+        let per_page = options.per_page.unwrap_or(100);
+        let mut seen = HashSet::new();
+        let mut items = vec![];
+
+        for (campaign, is_pointup) in [(Some(true), None), (None, Some(true))] {
+            for page in 1.. {
+                let query = CircleQuery {
+                    per_page: Some(per_page),
+                    page: Some(page),
+                    campaign,
+                    is_pointup,
+                    ..options.clone()
+                };
+                let query_path = query.to_path(circle_id.as_str());
+                let html = c
+                    .get(&query_path)
+                    .await
+                    .map_err(|e| e.with_not_found_id(circle_id.as_str()))?;
+                let parsed = parse_circle_sales(&html);
+                let (page_items, count) =
+                    c.parse_context(parsed, &query_path, Some(circle_id.as_str()), &html)?;
+                items.extend(
+                    page_items
+                        .into_iter()
+                        .filter(|item| seen.insert(item.product.id.clone())),
+                );
+                if i64::from(page) * i64::from(per_page) >= i64::from(count) {
+                    break;
+                }
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Get all products of a circle, fetching all pages automatically. Products are deduplicated
+    /// by ID, and [`PageStream::total`] is the count shown on the circle page. For how pages are
+    /// fetched, see [`StreamOptions`].
+    ///
+    /// `page` of `options` is ignored and the stream starts from the first page.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlsite::{
+    ///     client::{circle::CircleQuery, paginate::StreamOptions},
+    ///     DlsiteClient,
+    /// };
+    /// use futures::StreamExt as _;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = DlsiteClient::default();
+    ///     let query = CircleQuery {
+    ///         per_page: Some(100),
+    ///         ..Default::default()
+    ///     };
+    ///     let mut stream = client.circle().products_stream(
+    ///         &"RG24350".parse().unwrap(),
+    ///         &query,
+    ///         StreamOptions::default(),
+    ///     );
+    ///     while let Some(item) = stream.next().await {
+    ///         println!("{}", item.unwrap().title);
+    ///     }
+    ///     println!("total: {:?}", stream.total());
+    /// }
+    /// ```
+    pub fn products_stream(
+        &self,
+        circle_id: &MakerId,
+        options: &CircleQuery,
+        stream_options: StreamOptions,
+    ) -> PageStream<SearchProductItem> {
+        let client = self.clone();
+        let circle_id = circle_id.clone();
+        let query = options.clone();
+        let fetch = move |page| {
+            let client = client.clone();
+            let circle_id = circle_id.clone();
+            let query = CircleQuery {
+                page: Some(page),
+                ..query.clone()
+            };
+            async move {
+                let result = client.get_circle(&circle_id, &query).await?;
+                Ok(Page {
+                    items: result.products,
+                    total: Some(result.count.max(0) as usize),
+                })
+            }
+        };
+
+        paginate(
+            stream_options,
+            options.per_page.map(|n| n as usize),
+            fetch,
+            |item| item.id.clone(),
+        )
+    }
+}
+
+/// Parse circle profile page into products and total count.
+pub(crate) fn parse_circle_html(html: &str) -> Result<(Vec<SearchProductItem>, i32)> {
+    let html = Html::parse_fragment(html);
+    let products_html = html
+        .select(&Selector::parse("#search_result_list").unwrap())
+        .next()
+        .to_field_error("#search_result_list", "Product list not found")?;
+
+    let count = parse_total_count(&html)?;
+
+    let products = parse_search_html(&products_html.html())?;
+
+    Ok((products, count))
+}
+
+/// Parse the total number of works of a circle page.
+fn parse_total_count(html: &Html) -> Result<i32> {
+    html.select(&Selector::parse(".page_total > strong").unwrap())
+        .next()
+        .to_field_error(".page_total > strong", "No total item count found")?
+        .text()
+        .next()
+        .to_field_error(".page_total > strong", "No total item count found 2")?
+        .parse()
+        .to_field_error(".page_total > strong", "Failed to parse total item count")
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::DlsiteClient;
+    use chrono::NaiveDate;
+
+    use crate::{
+        client::{
+            cassette::test_client,
+            stub::{StubResponse, StubServer},
+        },
+        DlsiteClient,
+    };
+
+    const PROFILE_HTML: &str = r#"<html><body>
+<div class="prof_maker">
+  <div class="prof_maker_name"><strong>テストサークル</strong><span class="prof_maker_name_en">(Test Circle)</span></div>
+  <div class="prof_maker_text"> Profile text </div>
+  <table class="prof_maker_info">
+    <tr><th>ホームページ</th><td><a href="https://example.com/">Home</a></td></tr>
+    <tr><th>X(Twitter)</th><td><a href="https://x.com/test">@test</a></td></tr>
+    <tr><th>pixiv</th><td><a href="/maniax/dlaf/=/link/profile">internal</a><a href="https://www.pixiv.net/users/1">pixiv</a></td></tr>
+    <tr><th>最新作発売日</th><td>2023年02月18日</td></tr>
+  </table>
+  <div class="btn_follow"><span class="follow_count">12,345</span></div>
+</div>
+<div class="page_total"><strong>42</strong>件中</div>
+</body></html>"#;
+
+    #[test]
+    fn parse_profile() {
+        let profile = super::parse_circle_profile(PROFILE_HTML).unwrap();
+        assert_eq!(profile.name, "テストサークル");
+        assert_eq!(profile.name_en.as_deref(), Some("Test Circle"));
+        assert_eq!(profile.profile.as_deref(), Some("Profile text"));
+        assert_eq!(profile.homepage.as_deref(), Some("https://example.com/"));
+        assert_eq!(
+            profile.social_links,
+            vec!["https://x.com/test", "https://www.pixiv.net/users/1"]
+        );
+        assert_eq!(profile.follower_count, Some(12345));
+        assert_eq!(profile.work_count, 42);
+        assert_eq!(profile.latest_release, NaiveDate::from_ymd_opt(2023, 2, 18));
+    }
+
+    #[tokio::test]
+    async fn get_profile_path() {
+        let server = StubServer::start(vec![StubResponse::ok(PROFILE_HTML)]).await;
+        let client = DlsiteClient::new(&server.url());
+        client
+            .circle()
+            .get_profile(&"RG24350".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            server.paths(),
+            vec!["/circle/profile/=/maker_id/RG24350.html?locale=ja_JP"]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_profile() {
+        let client = test_client("circle/get_profile");
+        let circle_id = "RG24350".parse().unwrap();
+        let profile = client.circle().get_profile(&circle_id).await.unwrap();
+
+        assert!(!profile.name.is_empty());
+        assert!(profile.latest_release.is_some());
+        let res = client
+            .circle()
+            .get_circle(&circle_id, &super::CircleQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(profile.work_count, res.count);
+    }
+
+    #[tokio::test]
+    async fn get_sales() {
+        // Record this cassette while the circle has works on campaign.
+        let client = test_client("circle/get_sales");
+        let sales = client
+            .circle()
+            .get_sales(&"RG24350".parse().unwrap(), &super::CircleQuery::default())
+            .await
+            .unwrap();
+
+        assert!(!sales.is_empty());
+        for sale in sales {
+            assert!(sale.discount_rate.is_some() || sale.point_up);
+            assert!(sale.sale_price <= sale.original_price);
+            if sale.discount_rate.is_some() {
+                assert!(sale.sale_price < sale.original_price);
+            }
+        }
+    }
 
     #[tokio::test]
     async fn get_circle_1() {
-        let client = DlsiteClient::default();
+        let client = test_client("circle/get_circle_1");
         let res = client
             .circle()
             .get_circle(
-                "RG24350",
+                &"RG24350".parse().unwrap(),
                 &super::CircleQuery {
                     ..Default::default()
                 },
@@ -76,7 +345,7 @@ mod tests {
         let res = client
             .circle()
             .get_circle(
-                "RG24350",
+                &"RG24350".parse().unwrap(),
                 &super::CircleQuery {
                     page: Some(2),
                     ..Default::default()
@@ -0,0 +1,85 @@
+use chrono::NaiveDate;
+use scraper::{Html, Selector};
+
+use super::parse_total_count;
+use crate::{client::product::labels::parse_release_date, error::Result, utils::ToParseError as _};
+
+/// Profile of a circle, shown in the header of the circle page.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CircleProfile {
+    pub name: String,
+    pub name_en: Option<String>,
+    /// Profile text written by the circle.
+    pub profile: Option<String>,
+    pub homepage: Option<String>,
+    /// Links to social media (X, pixiv, Ci-en, ...) other than the homepage.
+    pub social_links: Vec<String>,
+    /// Number of users following (adding to favorites) the circle.
+    pub follower_count: Option<i32>,
+    /// Number of works on the site.
+    pub work_count: i32,
+    pub latest_release: Option<NaiveDate>,
+}
+
+/// Parse the header of a circle page.
+pub(crate) fn parse_circle_profile(html: &str) -> Result<CircleProfile> {
+    let html = Html::parse_document(html);
+    let text = |selector: &str| {
+        html.select(&Selector::parse(selector).unwrap())
+            .next()
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+
+    let name = text(".prof_maker_name strong")
+        .to_field_error(".prof_maker_name strong", "No circle name found")?;
+    let name_en = text(".prof_maker_name_en")
+        .map(|s| s.trim_matches(['(', ')', '（', '）']).trim().to_string())
+        .filter(|s| !s.is_empty());
+    let profile = text(".prof_maker_text");
+    let follower_count = text(".follow_count")
+        .map(|s| s.replace(',', "").parse())
+        .transpose()
+        .to_field_error(".follow_count", "Failed to parse follower count")?;
+
+    let mut homepage = None;
+    let mut social_links = vec![];
+    let mut latest_release = None;
+    let link_selector = Selector::parse("a[href]").unwrap();
+    for row in html.select(&Selector::parse(".prof_maker_info tr").unwrap()) {
+        let (Some(th), Some(td)) = (
+            row.select(&Selector::parse("th").unwrap()).next(),
+            row.select(&Selector::parse("td").unwrap()).next(),
+        ) else {
+            continue;
+        };
+        let label = th.text().collect::<String>();
+        let links = td
+            .select(&link_selector)
+            .filter_map(|a| a.value().attr("href"))
+            .filter(|href| href.starts_with("http"))
+            .map(|href| href.to_string());
+        match label.trim() {
+            "ホームページ" | "Homepage" => {
+                let mut links = links;
+                homepage = links.next();
+                social_links.extend(links);
+            }
+            "最新作発売日" | "Latest release" => {
+                latest_release = parse_release_date(&td.text().collect::<String>());
+            }
+            _ => social_links.extend(links),
+        }
+    }
+
+    Ok(CircleProfile {
+        name,
+        name_en,
+        profile,
+        homepage,
+        social_links,
+        follower_count,
+        work_count: parse_total_count(&html)?,
+        latest_release,
+    })
+}
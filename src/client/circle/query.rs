@@ -2,7 +2,7 @@ use crate::interface::query::Order;
 
 use super::super::search::macros::*;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct CircleQuery {
     /// Display lang
     pub order: Option<Order>,
@@ -10,6 +10,10 @@ pub struct CircleQuery {
     /// 30, 50 or 100
     pub per_page: Option<u32>,
     pub page: Option<u32>,
+    /// Only works on discount campaign.
+    pub campaign: Option<bool>,
+    /// Only works on point-up campaign.
+    pub is_pointup: Option<bool>,
 }
 
 impl CircleQuery {
@@ -19,6 +23,10 @@ impl CircleQuery {
         push_option_array!(path, self, options);
         push_option!(path, self, per_page);
         push_option!(path, self, per_page);
+        if self.campaign == Some(true) {
+            path.push_str("/campaign/campaign");
+        }
+        push_option_bool!(path, self, is_pointup);
         // TODO: Investigate this
         path.push_str("/show_type/3/hd/1/without_order/1");
         push_option!(path, self, page);
@@ -53,6 +61,18 @@ mod tests {
         );
     }
     #[test]
+    fn circle_param_campaign() {
+        assert_eq!(
+            "/circle/profile/=/campaign/campaign/is_pointup/1/show_type/3/hd/1/without_order/1/maker_id/RG24350.html",
+            CircleQuery {
+                campaign: Some(true),
+                is_pointup: Some(true),
+                ..Default::default()
+            }
+            .to_path("RG24350")
+        );
+    }
+    #[test]
     fn circle_param_2() {
         assert_eq!(
             "/circle/profile/=/show_type/3/hd/1/without_order/1/page/2/maker_id/RG24350.html/order/price",
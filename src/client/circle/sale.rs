@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use regex::Regex;
+use scraper::{Html, Selector};
+
+use super::parse_circle_html;
+use crate::{
+    client::search::SearchProductItem,
+    error::Result,
+    interface::id::ProductId,
+    utils::{jst, ToParseError as _},
+};
+
+/// A work of a circle which is on discount or point-up campaign.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CircleSaleItem {
+    pub product: SearchProductItem,
+    /// Discount rate in percent, like `30` for 30% off. `None` if not discounted.
+    pub discount_rate: Option<i32>,
+    /// Whether the work is on point-up campaign.
+    pub point_up: bool,
+    pub sale_price: i32,
+    pub original_price: i32,
+    /// End of the campaign. If the page shows only the date, the time is 23:59:59 JST.
+    pub campaign_end: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Default)]
+struct Campaign {
+    discount_rate: Option<i32>,
+    point_up: bool,
+    end: Option<DateTime<FixedOffset>>,
+}
+
+/// Parse a circle page into works on campaign and total count of works.
+pub(crate) fn parse_circle_sales(html: &str) -> Result<(Vec<CircleSaleItem>, i32)> {
+    let (products, count) = parse_circle_html(html)?;
+    let mut campaigns = parse_campaigns(html)?;
+
+    let items = products
+        .into_iter()
+        .filter_map(|product| {
+            let campaign = campaigns.remove(&product.id).unwrap_or_default();
+            if product.price_sale.is_none()
+                && campaign.discount_rate.is_none()
+                && !campaign.point_up
+            {
+                return None;
+            }
+            Some(CircleSaleItem {
+                discount_rate: campaign.discount_rate,
+                point_up: campaign.point_up,
+                sale_price: product.price_sale.unwrap_or(product.price_original),
+                original_price: product.price_original,
+                campaign_end: campaign.end,
+                product,
+            })
+        })
+        .collect();
+
+    Ok((items, count))
+}
+
+fn parse_campaigns(html: &str) -> Result<HashMap<ProductId, Campaign>> {
+    let html = Html::parse_fragment(html);
+    let rate_re = Regex::new(r"(\d+)\s*%").unwrap();
+    let mut campaigns = HashMap::new();
+
+    for item in html.select(&Selector::parse("#search_result_img_box > li").unwrap()) {
+        let id = item
+            .select(&Selector::parse("div[data-product_id]").unwrap())
+            .next()
+            .and_then(|e| e.value().attr("data-product_id"))
+            .to_field_error("div[data-product_id]", "Failed to find product id")?
+            .parse::<ProductId>()
+            .to_field_error("div[data-product_id]", "Failed to parse product id")?;
+        let text = |selector: &str| {
+            item.select(&Selector::parse(selector).unwrap())
+                .next()
+                .map(|e| e.text().collect::<String>())
+        };
+
+        campaigns.insert(
+            id,
+            Campaign {
+                discount_rate: text(".icon_campaign.type_sale")
+                    .and_then(|t| rate_re.captures(&t)?[1].parse().ok()),
+                point_up: item
+                    .select(&Selector::parse(".icon_campaign.type_pointup").unwrap())
+                    .next()
+                    .is_some(),
+                end: text(".period_date").and_then(|t| parse_campaign_end(&t)),
+            },
+        );
+    }
+
+    Ok(campaigns)
+}
+
+/// Parse the end of a campaign, like `2023年03月01日 23時59分 まで` or `2023年03月01日まで`.
+fn parse_campaign_end(text: &str) -> Option<DateTime<FixedOffset>> {
+    let re = Regex::new(
+        r"(\d{4})\s*年\s*(\d{1,2})\s*月\s*(\d{1,2})\s*日(?:\s*(\d{1,2})\s*時(?:\s*(\d{1,2})\s*分)?)?",
+    )
+    .unwrap();
+    let c = re.captures(text)?;
+    let date = NaiveDate::from_ymd_opt(c[1].parse().ok()?, c[2].parse().ok()?, c[3].parse().ok()?)?;
+    let end = match c.get(4) {
+        Some(hour) => date.and_hms_opt(
+            hour.as_str().parse().ok()?,
+            c.get(5).map_or(Some(0), |m| m.as_str().parse().ok())?,
+            0,
+        ),
+        None => date.and_hms_opt(23, 59, 59),
+    }?;
+    end.and_local_timezone(jst()).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_campaign_end, parse_circle_sales};
+
+    fn item(id: &str, price: &str, campaign: &str) -> String {
+        format!(
+            r#"<li>
+  <div data-product_id="{id}"></div>
+  <div class="work_thumb_inner"><img src="//img.dlsite.jp/{id}.jpg"></div>
+  <div class="work_name"><a title="Work {id}"></a></div>
+  <div class="maker_name"><a href="https://www.dlsite.com/maniax/circle/profile/=/maker_id/RG24350.html">Circle</a></div>
+  <div class="work_category type_SOU"></div>
+  <div class="work_price_wrap">{price}</div>
+  {campaign}
+</li>"#
+        )
+    }
+
+    #[test]
+    fn circle_sales() {
+        let html = format!(
+            r#"<div id="search_result_list"><ul id="search_result_img_box">{}{}{}</ul></div>
+<div class="page_total"><strong>3</strong></div>"#,
+            item(
+                "RJ01000001",
+                r#"<span class="work_price"><span class="work_price_base">770</span></span>
+                   <span class="strike"><span class="work_price_base">1,100</span></span>"#,
+                r#"<span class="icon_campaign type_sale">30%OFF</span>
+                   <span class="period_date">2023年03月01日 23時59分 まで</span>"#,
+            ),
+            item(
+                "RJ01000002",
+                r#"<span class="work_price"><span class="work_price_base">1,320</span></span>"#,
+                "",
+            ),
+            item(
+                "RJ01000003",
+                r#"<span class="work_price"><span class="work_price_base">990</span></span>"#,
+                r#"<span class="icon_campaign type_pointup">20%還元</span>"#,
+            ),
+        );
+
+        let (items, count) = parse_circle_sales(&html).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(items.len(), 2);
+
+        assert_eq!(items[0].product.id, "RJ01000001");
+        assert_eq!(items[0].discount_rate, Some(30));
+        assert!(!items[0].point_up);
+        assert_eq!(items[0].sale_price, 770);
+        assert_eq!(items[0].original_price, 1100);
+        assert_eq!(
+            items[0].campaign_end.unwrap().to_rfc3339(),
+            "2023-03-01T23:59:00+09:00"
+        );
+
+        assert_eq!(items[1].product.id, "RJ01000003");
+        assert_eq!(items[1].discount_rate, None);
+        assert!(items[1].point_up);
+        assert_eq!(items[1].sale_price, 990);
+        assert_eq!(items[1].campaign_end, None);
+    }
+
+    #[test]
+    fn campaign_end() {
+        let end = |text| parse_campaign_end(text).map(|end| end.to_rfc3339());
+        assert_eq!(
+            end("2023年03月01日 23時59分 まで").as_deref(),
+            Some("2023-03-01T23:59:00+09:00")
+        );
+        assert_eq!(
+            end("2023年3月1日 14時まで").as_deref(),
+            Some("2023-03-01T14:00:00+09:00")
+        );
+        assert_eq!(
+            end("2023年03月01日まで").as_deref(),
+            Some("2023-03-01T23:59:59+09:00")
+        );
+        assert_eq!(end("期間限定"), None);
+    }
+}
@@ -1,15 +1,110 @@
-use crate::error::Result;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
+use reqwest::{header::HeaderMap, StatusCode};
+
+use crate::{
+    error::{ParseError, Result},
+    interface::{locale::Locale, site::Site},
+    DlsiteError,
+};
+
+use self::{
+    cache::CacheConfig,
+    rate_limit::RateLimiter,
+    retry::RetryPolicy,
+    transport::{Request, ReqwestTransport, Response, Transport},
+    unknown_field::{ReportBuffer, UnknownFieldPolicy, UnknownFieldReport},
+};
+
+pub mod builder;
+pub mod cache;
+pub mod cassette;
 pub mod circle;
+pub mod paginate;
 pub mod product;
 pub mod product_api;
+pub mod ranking;
+pub mod rate_limit;
+pub mod retry;
+pub mod reviewer;
 pub mod search;
+#[cfg(test)]
+mod stub;
+pub mod transport;
+pub mod unknown_field;
 
 /// API client for DLsite.
+///
+/// The client is cheap to clone: clones share the same transport, rate limiter and cache.
 #[derive(Clone, Debug)]
 pub struct DlsiteClient {
-    client: reqwest::Client,
-    base_url: String,
+    inner: Arc<ClientInner>,
+    use_cache: bool,
+    site: Option<Site>,
+}
+
+#[derive(Debug)]
+struct ClientInner {
+    transport: Arc<dyn Transport>,
+    /// Base URL without the site, like `https://www.dlsite.com`.
+    origin: String,
+    /// Site of the base URL. `None` if the base URL does not end with a site.
+    default_site: Option<Site>,
+    headers: HeaderMap,
+    locale: Locale,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    cache: Option<CacheConfig>,
+    dump_dir: Option<PathBuf>,
+    parse_mode: ParseMode,
+    unknown_field_policy: UnknownFieldPolicy,
+    unknown_field_reports: Mutex<ReportBuffer>,
+}
+
+/// Kind of DLsite endpoint a request is sent to.
+///
+/// Used to give each endpoint its own rate limit budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// HTML pages such as product pages and circle profiles.
+    Html,
+    /// Ajax APIs for the web site, such as `/product/info/ajax` and `/fsr/ajax`.
+    Ajax,
+    /// Review API (`/api/review`).
+    Review,
+    /// Product API for apps (`/api/=/product.json`).
+    ProductApi,
+}
+
+impl Endpoint {
+    /// Guess the endpoint from a URL or a path.
+    pub fn from_url(url: &str) -> Self {
+        if url.contains("/api/review") {
+            Self::Review
+        } else if url.contains("/api/=/product.json") {
+            Self::ProductApi
+        } else if url.contains("/ajax") {
+            Self::Ajax
+        } else {
+            Self::Html
+        }
+    }
+}
+
+/// How strictly responses are parsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ParseMode {
+    /// Keep what this crate doesn't know in the result, such as
+    /// [`ProductHtml::extra`](product::html::ProductHtml::extra).
+    #[default]
+    Lenient,
+    /// Fail when the response has something this crate doesn't know. Useful to detect changes of
+    /// DLsite.
+    Strict,
 }
 
 impl Default for DlsiteClient {
@@ -23,49 +118,541 @@ impl DlsiteClient {
     ///
     /// Typical base URL is `https://www.dlsite.com/maniax` and you should be able to access any
     /// products using this URL, so usually you don't use this method and just use the default.
+    ///
+    /// If the base URL ends with a [`Site`], requests for a product or circle are sent to the
+    /// site implied by its ID, and [`DlsiteClient::site`] can switch the site. Otherwise the base
+    /// URL is used as is unless a site is set by [`DlsiteClient::site`].
+    ///
+    /// To configure timeouts, proxy, headers and so on, use [`DlsiteClient::builder`].
     pub fn new(base_url: &str) -> Self {
-        let client = reqwest::Client::default();
+        Self::with_transport(base_url, ReqwestTransport::default())
+    }
+
+    /// Create a new DLsite client which sends requests through a custom [`Transport`].
+    pub fn with_transport(base_url: &str, transport: impl Transport + 'static) -> Self {
+        let (origin, default_site) = split_base_url(base_url);
         Self {
-            client,
-            base_url: base_url.to_string(),
+            inner: Arc::new(ClientInner {
+                transport: Arc::new(transport),
+                origin,
+                default_site,
+                headers: HeaderMap::new(),
+                locale: Locale::default(),
+                retry_policy: RetryPolicy::none(),
+                rate_limiter: None,
+                cache: None,
+                dump_dir: None,
+                parse_mode: ParseMode::default(),
+                unknown_field_policy: UnknownFieldPolicy::default(),
+                unknown_field_reports: Mutex::default(),
+            }),
+            use_cache: true,
+            site: None,
+        }
+    }
+
+    /// Create a builder to configure a client. For more information, see
+    /// [`builder::DlsiteClientBuilder`].
+    pub fn builder() -> builder::DlsiteClientBuilder {
+        builder::DlsiteClientBuilder::default()
+    }
+
+    /// Get a clone of this client which does not read responses from the cache.
+    ///
+    /// Fresh responses are still stored in the cache.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlsite::DlsiteClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = DlsiteClient::default();
+    ///     let product = client.without_cache().product().get_ajax(&"RJ01014447".parse().unwrap()).await.unwrap();
+    /// }
+    /// ```
+    pub fn without_cache(&self) -> Self {
+        Self {
+            use_cache: false,
+            ..self.clone()
+        }
+    }
+
+    /// Get a clone of this client which sends all requests to `site`.
+    ///
+    /// Without this, requests for a product or circle are sent to the site implied by its ID
+    /// (keeping the default site if it shares IDs, e.g. [`Site::Home`] for `RJ` products), and
+    /// other requests such as search are sent to the default site.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlsite::{interface::site::Site, DlsiteClient};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = DlsiteClient::default();
+    ///     let product = client.site(Site::Home).product().get_ajax(&"RJ01014447".parse().unwrap()).await.unwrap();
+    /// }
+    /// ```
+    pub fn site(&self, site: Site) -> Self {
+        Self {
+            site: Some(site),
+            ..self.clone()
+        }
+    }
+
+    /// Site which requests without an ID are sent to, if known.
+    pub fn current_site(&self) -> Option<Site> {
+        self.site.or(self.inner.default_site)
+    }
+
+    /// Base URL which paths given to [`DlsiteClient::get`] are appended to.
+    pub fn base_url(&self) -> String {
+        match self.current_site() {
+            Some(site) => format!("{}/{site}", self.inner.origin),
+            None => self.inner.origin.clone(),
+        }
+    }
+
+    /// Get a clone of this client which sends requests to the site for an ID which implies
+    /// `site`. See [`DlsiteClient::site`].
+    pub(crate) fn for_id_site(&self, site: Site) -> Self {
+        match (self.site, self.inner.default_site) {
+            (Some(_), _) | (None, None) => self.clone(),
+            (None, Some(default)) if default.shares_ids_with(site) => self.clone(),
+            (None, Some(_)) => self.site(site),
+        }
+    }
+
+    /// Warn if the site reported by DLsite differs from the site explicitly requested.
+    pub(crate) fn check_site(&self, id: &str, site_id: &str) {
+        if let Some(site) = self.site {
+            if site.to_string() != site_id {
+                tracing::warn!("{id} was requested on {site} but DLsite reports {site_id}");
+            }
+        }
+    }
+
+    /// Locale of pages and APIs requested by this client.
+    pub fn locale(&self) -> Locale {
+        self.inner.locale
+    }
+
+    /// How strictly responses are parsed.
+    pub fn parse_mode(&self) -> ParseMode {
+        self.inner.parse_mode
+    }
+
+    /// Take reports of unknown fields collected so far by [`UnknownFieldPolicy::Collect`].
+    ///
+    /// Reports are shared by all clones of this client and its sub-clients. Each report has the
+    /// URL and ID of its request to tell which call it came from.
+    pub fn take_unknown_field_reports(&self) -> Vec<UnknownFieldReport> {
+        self.inner.unknown_field_reports.lock().unwrap().take()
+    }
+
+    /// Handle unknown fields found in a response to `path` according to the policy.
+    pub(crate) fn check_unknown_fields(
+        &self,
+        path: &str,
+        id: Option<&str>,
+        paths: Vec<String>,
+    ) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        // Other policies hand the paths to the caller instead.
+        #[cfg(feature = "unknown-field-log")]
+        if self.inner.unknown_field_policy == UnknownFieldPolicy::Ignore {
+            for path in &paths {
+                tracing::error!(
+                    "Ignored path: '{path}' for '{}'. Please report this to https://github.com/ozonezone/dlsite-rs",
+                    id.unwrap_or_default()
+                );
+            }
+        }
+        match self.inner.unknown_field_policy {
+            UnknownFieldPolicy::Ignore => Ok(()),
+            UnknownFieldPolicy::Collect => {
+                let url = self.url_for(path);
+                self.inner
+                    .unknown_field_reports
+                    .lock()
+                    .unwrap()
+                    .push(UnknownFieldReport {
+                        endpoint: Endpoint::from_url(&url),
+                        url,
+                        id: id.map(|id| id.to_string()),
+                        paths,
+                    });
+                Ok(())
+            }
+            UnknownFieldPolicy::Fail => Err(ParseError::new("Unknown fields found")
+                .field(&paths.join(", "))
+                .into()),
+        }
+    }
+
+    /// URL of `path`, which may be a full URL already.
+    fn url_for(&self, path: &str) -> String {
+        if path.starts_with("http") {
+            path.to_string()
+        } else {
+            format!("{}{}", self.base_url(), path)
         }
     }
 
+    /// Send a raw request through the transport of this client.
+    ///
+    /// Unlike other methods, this does not add default headers nor retry.
+    pub async fn send(&self, request: Request) -> Result<Response> {
+        self.inner.transport.send(request).await
+    }
+
+    /// Make a GET request with default headers and the locale, retrying transient failures
+    /// according to the retry policy and waiting for the rate limiter.
+    pub(crate) async fn fetch(&self, url: &str) -> Result<Response> {
+        let url = &with_locale(url, self.inner.locale);
+        let endpoint = Endpoint::from_url(url);
+        let host = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_string()))
+            .unwrap_or_default();
+        let mut attempt = 1;
+        loop {
+            if let Some(rate_limiter) = &self.inner.rate_limiter {
+                rate_limiter.acquire(&host, endpoint).await;
+            }
+            let mut request = Request::get(url);
+            request.headers = self.inner.headers.clone();
+            let delay = match self.send(request).await {
+                Ok(res) => {
+                    match self.inner.retry_policy.delay_for_status(
+                        attempt,
+                        res.status,
+                        &res.headers,
+                    ) {
+                        Some(delay) => delay,
+                        None => return Ok(res),
+                    }
+                }
+                Err(e) => match self.inner.retry_policy.delay_for_error(attempt, &e) {
+                    Some(delay) => delay,
+                    None => return Err(e),
+                },
+            };
+            tracing::debug!("Retrying {url} in {delay:?} (attempt {attempt})");
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Add information about the response to a parse error, and dump the body if configured.
+    ///
+    /// JSON deserialization errors are converted to parse errors too.
+    pub(crate) fn parse_context<T>(
+        &self,
+        result: Result<T>,
+        path: &str,
+        id: Option<&str>,
+        body: &str,
+    ) -> Result<T> {
+        let mut e = match result {
+            Ok(v) => return Ok(v),
+            Err(DlsiteError::Parse(e)) => e,
+            Err(DlsiteError::SerdeJson(e)) => {
+                Box::new(ParseError::new("Failed to parse json").source(e))
+            }
+            Err(e) => return Err(e),
+        };
+        let url = self.url_for(path);
+        let endpoint = Endpoint::from_url(&url);
+        e.endpoint = Some(endpoint);
+        e.url = Some(url);
+        e.id = e.id.take().or(id.map(|id| id.to_string()));
+        e.set_snippet(body);
+
+        if let Some(dir) = &self.inner.dump_dir {
+            let millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or(0);
+            let ext = if endpoint == Endpoint::Html {
+                "html"
+            } else {
+                "json"
+            };
+            let file_name = format!("{millis}-{endpoint:?}-{}.{ext}", id.unwrap_or("unknown"));
+            let dump_path = dir.join(file_name);
+            match std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&dump_path, body)) {
+                Ok(_) => e.dump_path = Some(dump_path),
+                Err(err) => tracing::warn!("Failed to dump response body: {err}"),
+            }
+        }
+
+        Err(DlsiteError::Parse(e))
+    }
+
+    /// Convert a response to its body, or to an error if the status is not successful or the
+    /// page is not the expected content.
+    fn check_response(url: &str, res: Response) -> Result<String> {
+        let status = res.status;
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(DlsiteError::RateLimited {
+                retry_after: retry::parse_retry_after(&res.headers),
+            });
+        }
+        if status == StatusCode::SERVICE_UNAVAILABLE && is_maintenance_page(&res.body) {
+            return Err(DlsiteError::Maintenance);
+        }
+        if !status.is_success() {
+            return Err(DlsiteError::HttpStatus {
+                code: status.as_u16(),
+                url: url.to_string(),
+            });
+        }
+        if Endpoint::from_url(url) == Endpoint::Html && is_age_gate_page(&res.body) {
+            return Err(DlsiteError::AgeGate);
+        }
+        Ok(res.body)
+    }
+
     /// Convenient method to make a http GET request using the client.
+    ///
+    /// Returns an error if the response status is not successful.
     pub async fn get(&self, path: &str) -> Result<String> {
-        let url = format!("{}{}", self.base_url, path);
-        let body = self.client.get(&url).send().await?.text().await?;
-        Ok(body)
+        let url = format!("{}{}", self.base_url(), path);
+        self.get_raw(&url).await
     }
 
     /// Similar to `get`, but this method does not prepend the base URL.
     pub async fn get_raw(&self, url: &str) -> Result<String> {
-        let body = self.client.get(url).send().await?.text().await?;
+        let cache = self
+            .inner
+            .cache
+            .as_ref()
+            .and_then(|c| Some((c, c.ttl_for(Endpoint::from_url(url))?)));
+        let key = format!("{}:{}", self.inner.locale, url);
+        if let Some((config, _)) = cache {
+            if self.use_cache {
+                if let Some(body) = config.cache.get(&key) {
+                    return Ok(body);
+                }
+            }
+        }
+
+        let res = self.fetch(url).await?;
+        let body = Self::check_response(url, res)?;
+        if let Some((config, ttl)) = cache {
+            config.cache.put(&key, &body, ttl);
+        }
         Ok(body)
     }
 }
 
+/// Add `locale` query parameter to a URL unless it already has one.
+fn with_locale(url: &str, locale: Locale) -> String {
+    if url.contains("locale=") {
+        return url.to_string();
+    }
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{url}{separator}locale={locale}")
+}
+
+/// Split a base URL into the origin and the site, if it ends with one.
+fn split_base_url(base_url: &str) -> (String, Option<Site>) {
+    let base_url = base_url.trim_end_matches('/');
+    if let Some((origin, last)) = base_url.rsplit_once('/') {
+        if let Ok(site) = last.parse::<Site>() {
+            return (origin.to_string(), Some(site));
+        }
+    }
+    (base_url.to_string(), None)
+}
+
+fn page_title(body: &str) -> Option<&str> {
+    let start = body.find("<title>")? + "<title>".len();
+    let end = body[start..].find("</title>")? + start;
+    Some(&body[start..end])
+}
+
+fn is_maintenance_page(body: &str) -> bool {
+    body.contains("メンテナンス") || body.to_lowercase().contains("maintenance")
+}
+
+fn is_age_gate_page(body: &str) -> bool {
+    page_title(body).is_some_and(|title| {
+        title.contains("年齢認証") || title.to_lowercase().contains("age verification")
+    })
+}
+
 /// These methods return a “sub-client”.
-/// The sub-client has a DlsiteClient reference inside and has implementations of fetch and parse focused on certain purposes.
+/// The sub-client has a clone of DlsiteClient inside and has implementations of fetch and parse focused on certain purposes.
+/// Sub-clients are `'static`, so they can be moved into spawned tasks or stored in structs.
 impl DlsiteClient {
     /// Get a client to fetch product info using 'scraping' method. For more information, see [`product::ProductClient`].
     pub fn product(&self) -> product::ProductClient {
-        product::ProductClient { c: self }
+        product::ProductClient { c: self.clone() }
     }
 
     /// Get a client to fetch product info using 'api' method. For more information, see
     /// [`product_api::ProductApiClient`].
     pub fn product_api(&self) -> product_api::ProductApiClient {
-        product_api::ProductApiClient { c: self }
+        product_api::ProductApiClient { c: self.clone() }
     }
 
     /// Get a client to fetch circle info. For more information, see [`circle::CircleClient`].
     pub fn circle(&self) -> circle::CircleClient {
-        circle::CircleClient { c: self }
+        circle::CircleClient { c: self.clone() }
+    }
+
+    /// Get a client to fetch rankings. For more information, see [`ranking::RankingClient`].
+    pub fn ranking(&self) -> ranking::RankingClient {
+        ranking::RankingClient { c: self.clone() }
+    }
+
+    /// Get a client to fetch reviews of a reviewer. For more information, see
+    /// [`reviewer::ReviewerClient`].
+    pub fn reviewer(&self) -> reviewer::ReviewerClient {
+        reviewer::ReviewerClient { c: self.clone() }
     }
 
     /// Get a client to search things. For more information, see [`search::SearchClient`].
     pub fn search(&self) -> search::SearchClient {
-        search::SearchClient { c: self }
+        search::SearchClient { c: self.clone() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{
+        stub::{StubResponse, StubServer},
+        Endpoint,
+    };
+    use crate::{interface::site::Site, DlsiteClient, DlsiteError};
+
+    #[tokio::test]
+    async fn status_errors() {
+        let server = StubServer::start(vec![
+            StubResponse::status(429).header("Retry-After", "5"),
+            StubResponse::status(500),
+            StubResponse::Http {
+                status: 503,
+                headers: vec![],
+                body: "<title>メンテナンス中</title>".to_string(),
+                path: None,
+            },
+            StubResponse::ok("<html><head><title>年齢認証 | DLsite</title></head></html>"),
+        ])
+        .await;
+        let client = DlsiteClient::new(&server.url());
+
+        assert!(matches!(
+            client.get("/a").await,
+            Err(DlsiteError::RateLimited { retry_after: Some(d) }) if d == Duration::from_secs(5)
+        ));
+        assert!(matches!(
+            client.get("/b").await,
+            Err(DlsiteError::HttpStatus { code: 500, url }) if url == format!("{}/b", server.url())
+        ));
+        assert!(matches!(
+            client.get("/c").await,
+            Err(DlsiteError::Maintenance)
+        ));
+        assert!(matches!(
+            client.get("/work/=/product_id/RJ01014447.html").await,
+            Err(DlsiteError::AgeGate)
+        ));
+    }
+
+    #[tokio::test]
+    async fn parse_error_context() {
+        let server = StubServer::start(vec![StubResponse::ok("<html><body></body></html>")]).await;
+        let dir = std::env::temp_dir().join(format!("dlsite-dump-test-{}", std::process::id()));
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .dump_parse_failures(&dir)
+            .build()
+            .unwrap();
+
+        let Err(DlsiteError::Parse(e)) = client
+            .product()
+            .get_html(&"RJ01014447".parse().unwrap())
+            .await
+        else {
+            panic!("Expected parse error");
+        };
+        assert_eq!(e.endpoint, Some(Endpoint::Html));
+        assert_eq!(e.id.as_deref(), Some("RJ01014447"));
+        assert_eq!(e.field.as_deref(), Some("#work_maker .maker_name a"));
+        assert_eq!(
+            e.url,
+            Some(format!("{}/work/=/product_id/RJ01014447", server.url()))
+        );
+        assert_eq!(e.snippet.as_deref(), Some("<html><body></body></html>"));
+        let dump_path = e.dump_path.expect("Expected dump path");
+        assert_eq!(
+            std::fs::read_to_string(&dump_path).unwrap(),
+            "<html><body></body></html>"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sub_client_in_task() {
+        let server = StubServer::start(vec![StubResponse::status(404)]).await;
+        let product = DlsiteClient::new(&server.url()).product();
+
+        let handle =
+            tokio::spawn(async move { product.get_html(&"RJ01014447".parse().unwrap()).await });
+        assert!(matches!(
+            handle.await.unwrap(),
+            Err(DlsiteError::NotFound { id }) if id == "RJ01014447"
+        ));
+    }
+
+    #[tokio::test]
+    async fn site_routing() {
+        let server = StubServer::start(vec![StubResponse::status(404); 5]).await;
+        let client = DlsiteClient::new(&format!("{}/maniax/", server.url()));
+        let rj = "RJ01014447".parse().unwrap();
+        let vj = "VJ01000513".parse().unwrap();
+
+        assert_eq!(client.base_url(), format!("{}/maniax", server.url()));
+        let _ = client.product().get_html(&rj).await;
+        let _ = client.product().get_html(&vj).await;
+        let _ = client.site(Site::Home).product().get_html(&vj).await;
+
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .site(Site::Home)
+            .build()
+            .unwrap();
+        let _ = client.product().get_html(&rj).await;
+        let _ = client.product_api().get(&vj).await;
+
+        assert_eq!(
+            server.paths(),
+            vec![
+                "/maniax/work/=/product_id/RJ01014447?locale=ja_JP",
+                "/pro/work/=/product_id/VJ01000513?locale=ja_JP",
+                "/home/work/=/product_id/VJ01000513?locale=ja_JP",
+                "/home/work/=/product_id/RJ01014447?locale=ja_JP",
+                "/pro/api/=/product.json?workno=VJ01000513&locale=ja_JP",
+            ]
+        );
+    }
+
+    #[test]
+    fn base_url_without_site() {
+        let client = DlsiteClient::new("http://localhost:8080/");
+        assert_eq!(client.current_site(), None);
+        assert_eq!(client.base_url(), "http://localhost:8080");
+        assert_eq!(
+            client.site(Site::Books).base_url(),
+            "http://localhost:8080/books"
+        );
     }
 }
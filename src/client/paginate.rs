@@ -0,0 +1,353 @@
+//! Streams which fetch pages automatically. For more information, see [`StreamOptions`].
+
+use std::{
+    collections::HashSet,
+    future::Future,
+    hash::Hash,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    task::{Context, Poll},
+};
+
+use futures::{
+    future::ready,
+    stream::{self, BoxStream},
+    Stream, StreamExt as _,
+};
+
+use crate::error::Result;
+
+/// Options for streams which fetch pages automatically, like
+/// [`SearchClient::search_product_stream`](super::search::SearchClient::search_product_stream).
+///
+/// Pages are fetched lazily: nothing is requested until the stream is polled, and the next pages
+/// are requested when the items already fetched run out. The stream ends when all pages up to the
+/// total count of the first page are fetched, a page is empty, `max_items` items are yielded or an
+/// error is yielded. For other stop conditions, see [`PageStream::stop_when`].
+///
+/// Results may shift between pages while the stream is consumed (e.g. a new product is released).
+/// Items already yielded are skipped if they appear again, so each item is yielded at most once.
+/// Shifts the other way can't be avoided: if an item on an earlier page is removed, the item
+/// which moves over the page boundary is never yielded. [`PageStream::missed`] reports how many
+/// items fell short of the total, so run the stream again if every item matters.
+#[derive(Debug, Clone, Copy)]
+pub struct StreamOptions {
+    /// Number of pages requested at once. Default is 1.
+    pub prefetch: usize,
+    /// Maximum number of items to yield. Default is unlimited.
+    pub max_items: Option<usize>,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        Self {
+            prefetch: 1,
+            max_items: None,
+        }
+    }
+}
+
+/// Stream of items of all pages. Items are `Result<T>`, and the stream ends after an error.
+pub struct PageStream<T> {
+    inner: BoxStream<'static, Result<T>>,
+    total: Arc<OnceLock<usize>>,
+    missed: Arc<OnceLock<usize>>,
+}
+
+impl<T> PageStream<T> {
+    /// Total count of items reported by the first page. `None` until the first page is fetched,
+    /// or if the endpoint doesn't report it.
+    ///
+    /// Duplicates are skipped and `max_items` is applied, so the number of yielded items may
+    /// differ from this.
+    pub fn total(&self) -> Option<usize> {
+        self.total.get().copied()
+    }
+
+    /// Number of items fewer than [`total`](Self::total) which were yielded, e.g. because results
+    /// shifted back while the stream was consumed. `Some(0)` if none were missed.
+    ///
+    /// `None` until the stream reaches the last page, if it ended early by an error, `max_items`
+    /// or [`stop_when`](Self::stop_when), or if the total count is unknown.
+    pub fn missed(&self) -> Option<usize> {
+        self.missed.get().copied()
+    }
+
+    /// End the stream at the first item for which `stop` returns `true`. The item is not yielded,
+    /// and no more pages are fetched.
+    ///
+    /// # Example
+    /// Stop at reviews older than a date, when reviews are sorted from the newest:
+    /// ```no_run
+    /// # use dlsite::{client::{paginate::StreamOptions, product::review::ReviewStreamOptions}, DlsiteClient};
+    /// # fn f(client: DlsiteClient, since: chrono::DateTime<chrono::FixedOffset>) {
+    /// let stream = client
+    ///     .product()
+    ///     .review_stream(
+    ///         &"RJ01014447".parse().unwrap(),
+    ///         &ReviewStreamOptions::default(),
+    ///         StreamOptions::default(),
+    ///     )
+    ///     .stop_when(move |review| review.regist_date < since);
+    /// # }
+    /// ```
+    pub fn stop_when<F>(self, stop: F) -> Self
+    where
+        T: Send + 'static,
+        F: Fn(&T) -> bool + Send + 'static,
+    {
+        let inner = self
+            .inner
+            .take_while(move |item| ready(!matches!(item, Ok(item) if stop(item))))
+            .boxed();
+        Self {
+            inner,
+            total: self.total,
+            missed: self.missed,
+        }
+    }
+
+    /// Stream which yields only `error`, for arguments found invalid before any request.
+    pub(crate) fn failed(error: crate::DlsiteError) -> Self
+    where
+        T: Send + 'static,
+    {
+        Self {
+            inner: stream::once(ready(Err(error))).boxed(),
+            total: Arc::default(),
+            missed: Arc::default(),
+        }
+    }
+}
+
+impl<T> Stream for PageStream<T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl<T> std::fmt::Debug for PageStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PageStream")
+            .field("total", &self.total())
+            .field("missed", &self.missed())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A page of results and the total count of items, if the endpoint reports it.
+pub(crate) struct Page<T> {
+    pub items: Vec<T>,
+    pub total: Option<usize>,
+}
+
+/// Stream items of all pages. `fetch` is called with 1-based page numbers, and `key` identifies
+/// items to skip duplicates. If `per_page` is `None`, the number of items of the first page is
+/// used. If the total count is unknown, pages are fetched until an empty page.
+pub(crate) fn paginate<T, K, F, Fut>(
+    options: StreamOptions,
+    per_page: Option<usize>,
+    fetch: F,
+    key: fn(&T) -> K,
+) -> PageStream<T>
+where
+    T: Send + 'static,
+    K: Eq + Hash + Send + 'static,
+    F: Fn(u32) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = Result<Page<T>>> + Send + 'static,
+{
+    let total = Arc::new(OnceLock::new());
+    let first = fetch(1);
+    let total_ = total.clone();
+    let pages: BoxStream<'static, Result<Page<T>>> = stream::once(first)
+        .flat_map(move |first| match first {
+            Ok(first) => {
+                let last = match first.total {
+                    Some(total) => {
+                        total_.get_or_init(|| total);
+                        let per_page = per_page.unwrap_or(first.items.len()).max(1);
+                        total.div_ceil(per_page) as u32
+                    }
+                    None => u32::MAX,
+                };
+                let rest = stream::iter(2..=last)
+                    .map(fetch.clone())
+                    .buffered(options.prefetch.max(1));
+                stream::once(ready(Ok(first))).chain(rest).boxed()
+            }
+            Err(e) => stream::once(ready(Err(e))).boxed(),
+        })
+        .boxed();
+
+    let mut seen = HashSet::new();
+    let mut failed = false;
+    let missed = Arc::new(OnceLock::new());
+    let (total_, missed_) = (total.clone(), missed.clone());
+    let inner = pages
+        .take_while(|page| ready(!matches!(page, Ok(page) if page.items.is_empty())))
+        .flat_map(|page| match page {
+            Ok(page) => {
+                stream::iter(page.items.into_iter().map(|item| Some(Ok(item)))).left_stream()
+            }
+            Err(e) => stream::once(ready(Some(Err(e)))).right_stream(),
+        })
+        // `None` marks the end of the last page.
+        .chain(stream::once(ready(None)))
+        .scan((), move |_, item| {
+            if failed {
+                return ready(None);
+            }
+            let item = match item {
+                Some(Ok(item)) => seen.insert(key(&item)).then_some(Ok(item)),
+                Some(Err(e)) => {
+                    failed = true;
+                    Some(Err(e))
+                }
+                None => {
+                    if let Some(total) = total_.get() {
+                        missed_.get_or_init(|| total.saturating_sub(seen.len()));
+                    }
+                    None
+                }
+            };
+            ready(Some(item))
+        })
+        .filter_map(ready)
+        .take(options.max_items.unwrap_or(usize::MAX))
+        .boxed();
+
+    PageStream {
+        inner,
+        total,
+        missed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use futures::StreamExt as _;
+
+    use super::{paginate, Page, StreamOptions};
+    use crate::DlsiteError;
+
+    fn pages(
+        data: Vec<Vec<u32>>,
+        total: usize,
+    ) -> (
+        Arc<AtomicU32>,
+        impl Fn(u32) -> futures::future::Ready<crate::error::Result<Page<u32>>> + Clone,
+    ) {
+        let fetched = Arc::new(AtomicU32::new(0));
+        let f = fetched.clone();
+        let fetch = move |page: u32| {
+            f.fetch_add(1, Ordering::SeqCst);
+            futures::future::ready(match data.get(page as usize - 1) {
+                Some(items) => Ok(Page {
+                    items: items.clone(),
+                    total: Some(total),
+                }),
+                None => Err(DlsiteError::Server("no page".to_string())),
+            })
+        };
+        (fetched, fetch)
+    }
+
+    #[tokio::test]
+    async fn all_pages() {
+        let (fetched, fetch) = pages(vec![vec![1, 2], vec![3, 4], vec![5]], 5);
+        let mut stream = paginate(StreamOptions::default(), None, fetch, |i| *i);
+        assert_eq!(stream.total(), None);
+        let items: Vec<_> = stream.by_ref().map(|i| i.unwrap()).collect().await;
+        assert_eq!(items, vec![1, 2, 3, 4, 5]);
+        assert_eq!(stream.total(), Some(5));
+        assert_eq!(stream.missed(), Some(0));
+        assert_eq!(fetched.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn shifted_results() {
+        // 0 is inserted at the top while fetching, so 2 appears again on the second page.
+        let (_, fetch) = pages(vec![vec![1, 2], vec![2, 3], vec![4]], 5);
+        let items: Vec<_> = paginate(StreamOptions::default(), Some(2), fetch, |i| *i)
+            .map(|i| i.unwrap())
+            .collect()
+            .await;
+        assert_eq!(items, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn removed_results() {
+        // 1 is removed after the first page is fetched, so 3 moves to the first page and is
+        // missed.
+        let (_, fetch) = pages(vec![vec![1, 2], vec![4, 5], vec![]], 5);
+        let mut stream = paginate(StreamOptions::default(), Some(2), fetch, |i| *i);
+        let items: Vec<_> = stream.by_ref().map(|i| i.unwrap()).collect().await;
+        assert_eq!(items, vec![1, 2, 4, 5]);
+        assert_eq!(stream.missed(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn max_items() {
+        let (fetched, fetch) = pages(vec![vec![1, 2], vec![3, 4], vec![5]], 5);
+        let options = StreamOptions {
+            max_items: Some(3),
+            ..Default::default()
+        };
+        let mut stream = paginate(options, None, fetch, |i| *i);
+        let items: Vec<_> = stream.by_ref().map(|i| i.unwrap()).collect().await;
+        assert_eq!(items, vec![1, 2, 3]);
+        assert_eq!(fetched.load(Ordering::SeqCst), 2);
+        assert_eq!(stream.missed(), None);
+    }
+
+    #[tokio::test]
+    async fn stop_on_error() {
+        let (fetched, fetch) = pages(vec![vec![1, 2]], 6);
+        let options = StreamOptions {
+            prefetch: 2,
+            ..Default::default()
+        };
+        let mut stream = paginate(options, None, fetch, |i| *i);
+        let items: Vec<_> = stream.by_ref().collect().await;
+        assert_eq!(items.len(), 3);
+        assert!(items[2].is_err());
+        assert_eq!(stream.missed(), None);
+        assert_eq!(fetched.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn unknown_total() {
+        let fetch = |page: u32| {
+            let items = if page <= 2 {
+                vec![page * 10, page * 10 + 1]
+            } else {
+                vec![]
+            };
+            futures::future::ready(Ok(Page { items, total: None }))
+        };
+        let mut stream = paginate(StreamOptions::default(), None, fetch, |i| *i);
+        let items: Vec<_> = stream.by_ref().map(|i| i.unwrap()).collect().await;
+        assert_eq!(items, vec![10, 11, 20, 21]);
+        assert_eq!(stream.total(), None);
+        assert_eq!(stream.missed(), None);
+    }
+
+    #[tokio::test]
+    async fn stop_when() {
+        let (fetched, fetch) = pages(vec![vec![1, 2], vec![3, 4], vec![5]], 5);
+        let items: Vec<_> = paginate(StreamOptions::default(), None, fetch, |i| *i)
+            .stop_when(|i| *i >= 2)
+            .map(|i| i.unwrap())
+            .collect()
+            .await;
+        assert_eq!(items, vec![1]);
+        assert_eq!(fetched.load(Ordering::SeqCst), 1);
+    }
+}
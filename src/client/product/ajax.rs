@@ -3,7 +3,11 @@ use std::{collections::HashMap, str::FromStr as _};
 use serde::{Deserialize, Deserializer};
 use serde_json::Value;
 
-use crate::interface::product::WorkType;
+use crate::{
+    client::unknown_field,
+    error::Result,
+    interface::{id::ProductId, product::WorkType, site::Site},
+};
 
 #[derive(Debug, Clone, Deserialize)]
 #[cfg_attr(feature = "unknown-field-error", serde(deny_unknown_fields))]
@@ -217,6 +221,13 @@ pub struct ProductAjax {
     pub work_rentals: Vec<Value>,
 }
 
+impl ProductAjax {
+    /// Site of the product, parsed from `site_id`. `None` if the site is unknown to this crate.
+    pub fn site(&self) -> Option<Site> {
+        self.site_id.parse().ok()
+    }
+}
+
 fn deserialize_work_type<'de, D>(deserializer: D) -> std::result::Result<WorkType, D::Error>
 where
     D: Deserializer<'de>,
@@ -225,3 +236,16 @@ where
 
     WorkType::from_str(&s).map_err(serde::de::Error::custom)
 }
+
+/// Parse json returned by the ajax api, which is a map of product ID to product data.
+///
+/// Returns the products and paths of unknown fields.
+pub(crate) fn parse_ajax_json(
+    json_str: &str,
+) -> Result<(HashMap<ProductId, ProductAjax>, Vec<String>)> {
+    // Ajax api returns an empty array when no product is found.
+    if json_str.trim() == "[]" {
+        return Ok((HashMap::new(), vec![]));
+    }
+    unknown_field::from_json(json_str)
+}
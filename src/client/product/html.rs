@@ -5,20 +5,27 @@ use scraper::{ElementRef, Html, Selector};
 use url::Url;
 
 use crate::{
-    error::Result,
-    interface::{genre::Genre, product::AgeCategory},
+    client::ParseMode,
+    error::{ParseError, Result},
+    interface::{
+        genre::Genre,
+        id::MakerId,
+        locale::Locale,
+        product::AgeCategory,
+        url::{path_param, DlsiteUrl},
+    },
     utils::ToParseError,
     DlsiteError,
 };
 
-use super::ProductPeople;
+use super::{labels, ProductPeople};
 
 /// Product data got from html
 #[derive(Debug)]
 pub struct ProductHtml {
     pub released_at: NaiveDate,
     pub age_rating: Option<AgeCategory>,
-    pub circle_id: String,
+    pub circle_id: MakerId,
     pub circle_name: String,
     pub images: Vec<String>,
     pub people: ProductPeople,
@@ -39,29 +46,38 @@ pub struct ProductHtml {
     pub sys_req: Option<String>,
     pub coupling: Vec<String>,
     pub lang_refs: Vec<(String, String)>,
+    /// Rows of `#work_outline` table which this crate doesn't know, as label, text and link URLs.
+    /// Always empty in [`ParseMode::Strict`].
+    pub extra: Vec<(String, String, Vec<String>)>,
 }
 
-pub(super) fn parse_product_html(html: &Html) -> Result<ProductHtml> {
+/// Parse a product page. Labels of `#work_outline` table are read in `locale`.
+///
+/// Unknown rows of `#work_outline` table are kept in [`ProductHtml::extra`], or make an error in
+/// [`ParseMode::Strict`].
+pub(crate) fn parse_product_html(
+    html: &Html,
+    locale: Locale,
+    mode: ParseMode,
+) -> Result<ProductHtml> {
     let circle = html
         .select(&Selector::parse("#work_maker .maker_name a").unwrap())
         .next()
-        .to_parse_error("No circle found")?;
+        .to_field_error("#work_maker .maker_name a", "No circle found")?;
     let circle_name = circle
         .text()
         .next()
         .to_parse_error("No circle name found")?
         .to_string();
-    let circle_id = circle
+    let circle_url = circle
         .value()
         .attr("href")
-        .to_parse_error("No circle id found")?
-        .split('/')
-        .next_back()
-        .to_parse_error("Failed to parse circle id")?
-        .split('.')
-        .next()
-        .to_parse_error("Failed to parse circle id")?
-        .to_string();
+        .to_field_error("#work_maker .maker_name a", "No circle id found")?;
+    let circle_id = DlsiteUrl::parse(circle_url)
+        .to_field_error("#work_maker .maker_name a", "Failed to parse circle url")?
+        .maker_id()
+        .cloned()
+        .to_field_error("#work_maker .maker_name a", "Failed to parse circle id")?;
 
     let images: Vec<String> = html
         .select(&Selector::parse(".product-slider-data > div").unwrap())
@@ -73,18 +89,20 @@ pub(super) fn parse_product_html(html: &Html) -> Result<ProductHtml> {
         .collect();
 
     // work_outline_table
-    let mut work_outline_table = get_work_outline_table(html);
+    let mut work_outline_table = get_work_outline_table(html, locale);
     work_outline_table.remove("作者");
     work_outline_table.remove("声優");
     let file_size = work_outline_table
         .remove("ファイル容量")
         .and_then(|v| {
+            // Text is like "総計\u{a0}1.2GB" in any locale.
             v.select(&Selector::parse("div").unwrap()).next().map(|v| {
                 Some(
                     v.text()
                         .collect::<String>()
                         .trim()
-                        .strip_prefix("総計\u{a0}")?
+                        .split_once('\u{a0}')?
+                        .1
                         .to_owned(),
                 )
             })
@@ -166,22 +184,15 @@ pub(super) fn parse_product_html(html: &Html) -> Result<ProductHtml> {
             Ok::<_, DlsiteError>(
                 v.select(&Selector::parse("span").unwrap())
                     .next()
-                    .to_parse_error("No age rating found")?
+                    .to_field_error("年齢指定", "No age rating found")?
                     .inner_html(),
             )
         })
         .transpose()?;
     let age_rating = match age_rating {
-        Some(age_rating) => Some(match age_rating.as_str() {
-            "全年齢" => AgeCategory::General,
-            "R18" => AgeCategory::Adult,
-            "R-15" => AgeCategory::R15,
-            _ => {
-                return Err(DlsiteError::Parse(format!(
-                    "failed to convert {age_rating} to enum"
-                )))
-            }
-        }),
+        Some(age_rating) => Some(labels::parse_age_category(&age_rating).ok_or_else(|| {
+            ParseError::new(format!("failed to convert {age_rating} to enum")).field("年齢指定")
+        })?),
         None => None,
     };
 
@@ -190,17 +201,12 @@ pub(super) fn parse_product_html(html: &Html) -> Result<ProductHtml> {
 
     let released_at = work_outline_table
         .remove("販売日")
-        .to_parse_error("No released_at found")?
+        .to_field_error("販売日", "No released_at found")?
         .text()
         .next()
         .to_parse_error("No released_at found")?;
-    let released_at = regex::Regex::new(r"\d*年\d*月\d*日")
-        .unwrap()
-        .find(released_at)
-        .to_parse_error("Failed to parse released_at")?
-        .as_str();
-    let released_at = NaiveDate::parse_from_str(released_at, "%Y年%m月%d日")
-        .map_err(|_| DlsiteError::Parse("Failed to parse released_at".to_string()))?;
+    let released_at = labels::parse_release_date(released_at)
+        .to_field_error("販売日", "Failed to parse released_at")?;
     let genre = work_outline_table
         .remove("ジャンル")
         .map(|element| {
@@ -208,27 +214,44 @@ pub(super) fn parse_product_html(html: &Html) -> Result<ProductHtml> {
                 .select(&Selector::parse("a").unwrap())
                 .filter_map(|element| {
                     let name = element.text().next()?.to_string();
-                    let mut id = None;
-                    let mut next = false;
-                    element.value().attr("href")?.split('/').for_each(|s| {
-                        if next {
-                            id = Some(s.to_string());
-                            next = false;
-                        }
-                        if s == "genre" {
-                            next = true;
-                        }
-                    });
-                    id.map(|id| Genre { name, id })
+                    let segments: Vec<&str> = element.value().attr("href")?.split('/').collect();
+                    let id = path_param(&segments, "genre")?.to_string();
+                    Some(Genre { name, id })
                 })
                 .collect::<Vec<_>>()
         })
         .unwrap_or_default();
-    if !work_outline_table.is_empty() {
-        return Err(DlsiteError::Parse(format!(
+    if mode == ParseMode::Strict && !work_outline_table.is_empty() {
+        return Err(ParseError::new(format!(
             "failed to parse tags {:?}",
             work_outline_table.len()
-        )));
+        ))
+        .field(
+            &work_outline_table
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+        .into());
+    }
+    let extra = work_outline_rows(html, locale)
+        .into_iter()
+        .filter(|(label, _)| work_outline_table.contains_key(label))
+        .map(|(label, td)| {
+            let text = td.text().collect::<String>().trim().to_owned();
+            let links = td
+                .select(&Selector::parse("a").unwrap())
+                .filter_map(|a| Some(a.attr("href")?.to_owned()))
+                .collect();
+            (label, text, links)
+        })
+        .collect::<Vec<_>>();
+    if !extra.is_empty() {
+        tracing::debug!(
+            "Unknown rows in work outline: {:?}",
+            work_outline_table.keys()
+        );
     }
     Ok(ProductHtml {
         released_at,
@@ -236,7 +259,7 @@ pub(super) fn parse_product_html(html: &Html) -> Result<ProductHtml> {
         circle_id,
         circle_name,
         images,
-        people: parse_product_people(html)?,
+        people: parse_product_people(html, locale)?,
         genre,
         series,
         file_format,
@@ -254,11 +277,12 @@ pub(super) fn parse_product_html(html: &Html) -> Result<ProductHtml> {
         sys_req,
         coupling,
         lang_refs,
+        extra,
     })
 }
 
-pub(super) fn parse_product_people(html: &Html) -> Result<ProductPeople> {
-    let work_outline_table = get_work_outline_table(html);
+pub(super) fn parse_product_people(html: &Html, locale: Locale) -> Result<ProductPeople> {
+    let work_outline_table = get_work_outline_table(html, locale);
 
     macro_rules! get_people {
         ($key:literal) => {
@@ -282,18 +306,23 @@ pub(super) fn parse_product_people(html: &Html) -> Result<ProductPeople> {
     })
 }
 
-fn get_work_outline_table(html: &Html) -> HashMap<String, ElementRef> {
-    let mut map = HashMap::new();
+/// Get rows of `#work_outline` table keyed by their Japanese labels.
+fn get_work_outline_table(html: &Html, locale: Locale) -> HashMap<String, ElementRef<'_>> {
+    work_outline_rows(html, locale).into_iter().collect()
+}
+
+/// Get rows of `#work_outline` table in the document order, with their Japanese labels.
+fn work_outline_rows(html: &Html, locale: Locale) -> Vec<(String, ElementRef<'_>)> {
+    let mut rows = vec![];
     for element in html.select(&Selector::parse("#work_outline tr").unwrap()) {
         let th = element.select(&Selector::parse("th").unwrap()).next();
         let td = element.select(&Selector::parse("td").unwrap()).next();
         if let (Some(th), Some(td)) = (th, td) {
             let th = th.text().next();
             if let Some(th) = th {
-                let th = th.trim().to_string();
-                map.insert(th, td);
+                rows.push((labels::normalize(locale, th.trim()), td));
             }
         }
     }
-    map
+    rows
 }
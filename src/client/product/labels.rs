@@ -0,0 +1,179 @@
+//! Labels of `#work_outline` table of product pages in each locale.
+
+use chrono::NaiveDate;
+use regex::Regex;
+
+use crate::interface::{locale::Locale, product::AgeCategory};
+
+/// Pairs of a localized label and the Japanese label which the parser uses as a key.
+fn table(locale: Locale) -> &'static [(&'static str, &'static str)] {
+    match locale {
+        Locale::Japanese => &[],
+        Locale::English => &[
+            ("Release date", "販売日"),
+            ("Update information", "更新情報"),
+            ("Series name", "シリーズ名"),
+            ("Author", "作者"),
+            ("Scenario", "シナリオ"),
+            ("Illustration", "イラスト"),
+            ("Voice Actor", "声優"),
+            ("Music", "音楽"),
+            ("Age", "年齢指定"),
+            ("Product format", "作品形式"),
+            ("File format", "ファイル形式"),
+            ("Supported languages", "対応言語"),
+            ("Genre", "ジャンル"),
+            ("File size", "ファイル容量"),
+            ("Event", "イベント"),
+            ("Page count", "ページ数"),
+            ("System Requirements", "動作環境"),
+            ("Coupling", "カップリング"),
+            ("Other", "その他"),
+        ],
+        Locale::ChineseSimplified => &[
+            ("贩卖日", "販売日"),
+            ("更新信息", "更新情報"),
+            ("系列名", "シリーズ名"),
+            ("剧情", "シナリオ"),
+            ("插画", "イラスト"),
+            ("声优", "声優"),
+            ("音乐", "音楽"),
+            ("年龄指定", "年齢指定"),
+            ("作品类型", "作品形式"),
+            ("文件形式", "ファイル形式"),
+            ("对应语言", "対応言語"),
+            ("分类", "ジャンル"),
+            ("文件容量", "ファイル容量"),
+            ("活动", "イベント"),
+            ("页数", "ページ数"),
+            ("运行环境", "動作環境"),
+            ("配对", "カップリング"),
+            ("其他", "その他"),
+        ],
+        Locale::ChineseTraditional => &[
+            ("販賣日", "販売日"),
+            ("更新資訊", "更新情報"),
+            ("系列名", "シリーズ名"),
+            ("劇本", "シナリオ"),
+            ("插畫", "イラスト"),
+            ("聲優", "声優"),
+            ("音樂", "音楽"),
+            ("年齡指定", "年齢指定"),
+            ("作品形式", "作品形式"),
+            ("檔案形式", "ファイル形式"),
+            ("對應語言", "対応言語"),
+            ("分類", "ジャンル"),
+            ("檔案容量", "ファイル容量"),
+            ("活動", "イベント"),
+            ("頁數", "ページ数"),
+            ("運行環境", "動作環境"),
+            ("配對", "カップリング"),
+            ("其他", "その他"),
+        ],
+        Locale::Korean => &[
+            ("판매일", "販売日"),
+            ("갱신 정보", "更新情報"),
+            ("시리즈명", "シリーズ名"),
+            ("저자", "作者"),
+            ("시나리오", "シナリオ"),
+            ("일러스트", "イラスト"),
+            ("성우", "声優"),
+            ("음악", "音楽"),
+            ("연령 지정", "年齢指定"),
+            ("작품 형식", "作品形式"),
+            ("파일 형식", "ファイル形式"),
+            ("대응 언어", "対応言語"),
+            ("장르", "ジャンル"),
+            ("파일 용량", "ファイル容量"),
+            ("이벤트", "イベント"),
+            ("페이지 수", "ページ数"),
+            ("동작 환경", "動作環境"),
+            ("커플링", "カップリング"),
+            ("기타", "その他"),
+        ],
+    }
+}
+
+/// Convert a label in `locale` to the Japanese one. Unknown labels are returned as is.
+pub(super) fn normalize(locale: Locale, label: &str) -> String {
+    table(locale)
+        .iter()
+        .find(|(localized, _)| localized.eq_ignore_ascii_case(label))
+        .map(|(_, ja)| ja.to_string())
+        .unwrap_or_else(|| label.to_string())
+}
+
+/// Parse a release date, which is `2023年02月18日` (Japanese, Chinese), `2023년 02월 18일`
+/// (Korean), `Feb/18/2023` or `02/18/2023` (English).
+pub(crate) fn parse_release_date(text: &str) -> Option<NaiveDate> {
+    let re = Regex::new(r"(\d{4})\s*[年년]\s*(\d{1,2})\s*[月월]\s*(\d{1,2})\s*[日일]").unwrap();
+    if let Some(c) = re.captures(text) {
+        return NaiveDate::from_ymd_opt(c[1].parse().ok()?, c[2].parse().ok()?, c[3].parse().ok()?);
+    }
+    let re = Regex::new(r"([A-Za-z]{3,9})\.?/(\d{1,2})/(\d{4})").unwrap();
+    if let Some(c) = re.captures(text) {
+        const MONTHS: [&str; 12] = [
+            "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+        ];
+        let month = c[1].to_ascii_lowercase();
+        let month = MONTHS.iter().position(|m| month.starts_with(m))? + 1;
+        return NaiveDate::from_ymd_opt(c[3].parse().ok()?, month as u32, c[2].parse().ok()?);
+    }
+    let re = Regex::new(r"(\d{1,2})/(\d{1,2})/(\d{4})").unwrap();
+    let c = re.captures(text)?;
+    NaiveDate::from_ymd_opt(c[3].parse().ok()?, c[1].parse().ok()?, c[2].parse().ok()?)
+}
+
+/// Parse the age rating shown in any locale.
+pub(super) fn parse_age_category(text: &str) -> Option<AgeCategory> {
+    match text.trim().to_lowercase().as_str() {
+        "全年齢" | "全年龄" | "全年齡" | "all ages" | "전체 연령" | "전연령" => {
+            Some(AgeCategory::General)
+        }
+        "r18" | "r-18" | "18禁" | "adult" => Some(AgeCategory::Adult),
+        "r-15" | "r15" => Some(AgeCategory::R15),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{normalize, parse_age_category, parse_release_date};
+    use crate::interface::{locale::Locale, product::AgeCategory};
+
+    #[test]
+    fn normalize_label() {
+        assert_eq!(normalize(Locale::English, "Release date"), "販売日");
+        assert_eq!(normalize(Locale::English, "release date"), "販売日");
+        assert_eq!(normalize(Locale::ChineseSimplified, "分类"), "ジャンル");
+        assert_eq!(normalize(Locale::Japanese, "販売日"), "販売日");
+        assert_eq!(normalize(Locale::English, "Unknown"), "Unknown");
+    }
+
+    #[test]
+    fn release_date() {
+        let date = NaiveDate::from_ymd_opt(2023, 2, 18);
+        assert_eq!(parse_release_date("2023年02月18日 0時"), date);
+        assert_eq!(parse_release_date("2023년 02월 18일"), date);
+        assert_eq!(parse_release_date("02/18/2023"), date);
+        assert_eq!(parse_release_date("Feb/18/2023"), date);
+        assert_eq!(parse_release_date("February/18/2023"), date);
+        assert_eq!(
+            parse_release_date("Jul/17/2022"),
+            NaiveDate::from_ymd_opt(2022, 7, 17)
+        );
+        assert_eq!(parse_release_date("2023年2月18日"), date);
+        assert_eq!(parse_release_date("unknown"), None);
+    }
+
+    #[test]
+    fn age_category() {
+        assert_eq!(parse_age_category("全年齢"), Some(AgeCategory::General));
+        assert_eq!(parse_age_category("All Ages"), Some(AgeCategory::General));
+        assert_eq!(parse_age_category("R18"), Some(AgeCategory::Adult));
+        assert_eq!(parse_age_category("R-15"), Some(AgeCategory::R15));
+        assert_eq!(parse_age_category("?"), None);
+    }
+}
@@ -2,13 +2,17 @@
 
 use std::collections::HashMap;
 
+use futures::future::try_join_all;
+
+use super::paginate::{PageStream, StreamOptions};
 use crate::{
     error::Result,
     interface::{
         genre::Genre,
+        id::{MakerId, ProductId},
         product::{AgeCategory, WorkType},
+        site::Site,
     },
-    utils::ToParseError as _,
     DlsiteClient, DlsiteError,
 };
 use ajax::ProductAjax;
@@ -16,6 +20,7 @@ use chrono::NaiveDate;
 
 pub mod ajax;
 pub mod html;
+pub(crate) mod labels;
 pub mod review;
 #[cfg(test)]
 mod test;
@@ -43,20 +48,22 @@ mod test;
 ///
 /// So this client has a `get_all` method to do all 1-3 and get the complete information, and each method to do only each.
 #[derive(Clone, Debug)]
-pub struct ProductClient<'a> {
-    pub(crate) c: &'a DlsiteClient,
+pub struct ProductClient {
+    pub(crate) c: DlsiteClient,
 }
 
 /// A product on DLsite.
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Product {
-    pub id: String,
+    pub id: ProductId,
+    /// Site of the product. `None` if the site is unknown to this crate.
+    pub site: Option<Site>,
     pub title: String,
     pub work_type: WorkType,
     pub released_at: NaiveDate,
     pub age_rating: Option<AgeCategory>,
     pub genre: Vec<Genre>,
-    pub circle_id: String,
+    pub circle_id: MakerId,
     pub circle_name: String,
     pub price: i32,
     pub series: Option<String>,
@@ -81,11 +88,11 @@ pub struct ProductPeople {
     pub voice_actor: Option<Vec<String>>,
 }
 
-impl<'a> ProductClient<'a> {
+impl ProductClient {
     /// Get full information about a product. For more detail, see documentation of [`ProductClient`].
     ///
     /// # Arguments
-    /// * `product_id` - The product ID to get information about. Example: `RJ123456`. NOTE: This must be capitalized.
+    /// * `product_id` - The product ID to get information about. Example: `RJ123456`.
     ///
     /// # Example
     /// ```
@@ -93,19 +100,21 @@ impl<'a> ProductClient<'a> {
     /// #[tokio::main]
     /// async fn main() {
     ///     let client = DlsiteClient::default();
-    ///     let product = client.product().get_all("RJ123456").await.unwrap();
+    ///     let product = client.product().get_all(&"RJ123456".parse().unwrap()).await.unwrap();
     ///     println!("{:#?}", product);
     /// }
     /// ```
-    pub async fn get_all(&self, product_id: &str) -> Result<Product> {
+    pub async fn get_all(&self, product_id: &ProductId) -> Result<Product> {
+        let review_query = review::ReviewQuery::default();
         let (html_data, ajax_data, review_data) = tokio::try_join!(
             self.get_html(product_id),
             self.get_ajax(product_id),
-            self.get_review(product_id, 6, 1, true, review::ReviewSortOrder::New)
+            self.get_review(product_id, &review_query)
         )?;
 
         Ok(Product {
-            id: product_id.to_string(),
+            id: product_id.clone(),
+            site: ajax_data.site(),
             title: ajax_data.work_name,
             work_type: ajax_data.work_type,
             released_at: html_data.released_at,
@@ -130,23 +139,39 @@ impl<'a> ProductClient<'a> {
 
     /// Scrapes the HTML page of a product and parses it.
     #[tracing::instrument(err)]
-    pub async fn get_html(&self, product_id: &str) -> Result<html::ProductHtml> {
+    pub async fn get_html(&self, product_id: &ProductId) -> Result<html::ProductHtml> {
+        let c = self.c.for_id_site(product_id.site());
         let path = format!("/work/=/product_id/{}", product_id);
-        let html = self.c.get(&path).await?;
-        let html = scraper::Html::parse_document(&html);
+        let html = c
+            .get(&path)
+            .await
+            .map_err(|e| e.with_not_found_id(product_id.as_str()))?;
+        let parsed = html::parse_product_html(
+            &scraper::Html::parse_document(&html),
+            c.locale(),
+            c.parse_mode(),
+        );
 
-        html::parse_product_html(&html)
+        c.parse_context(parsed, &path, Some(product_id.as_str()), &html)
     }
 
     /// Fetch detailed product information using 'ajax api'.
-    pub async fn get_ajax(&self, product_id: &str) -> Result<ProductAjax> {
+    pub async fn get_ajax(&self, product_id: &ProductId) -> Result<ProductAjax> {
+        let c = self.c.for_id_site(product_id.site());
         let path = format!("/product/info/ajax?product_id={}", product_id);
-        let ajax_json_str = self.c.get(&path).await?;
+        let ajax_json_str = c.get(&path).await?;
 
-        let mut json: HashMap<String, ProductAjax> = serde_json::from_str(&ajax_json_str)?;
+        let parsed = ajax::parse_ajax_json(&ajax_json_str);
+        let (mut json, unknown) =
+            c.parse_context(parsed, &path, Some(product_id.as_str()), &ajax_json_str)?;
+        let checked = c.check_unknown_fields(&path, Some(product_id.as_str()), unknown);
+        c.parse_context(checked, &path, Some(product_id.as_str()), &ajax_json_str)?;
         let product = json
             .remove(product_id)
-            .ok_or_else(|| DlsiteError::Parse("Failed to parse ajax json".to_string()))?;
+            .ok_or_else(|| DlsiteError::NotFound {
+                id: product_id.to_string(),
+            })?;
+        c.check_site(product_id.as_str(), &product.site_id);
 
         Ok(product)
     }
@@ -154,65 +179,133 @@ impl<'a> ProductClient<'a> {
     /// Fetch detailed multiple products information using 'ajax api'.
     ///
     /// It is more efficient to use this method than calling `get_ajax` multiple times.
+    /// Products are grouped by the site of their ID, and one request is sent to each site.
     #[tracing::instrument(err)]
     pub async fn get_ajax_multiple(
         &self,
-        product_ids: Vec<&str>,
-    ) -> Result<HashMap<String, ProductAjax>> {
-        let path = format!("/product/info/ajax?product_id={}", product_ids.join(","));
-        let ajax_json_str = self.c.get(&path).await?;
+        product_ids: &[ProductId],
+    ) -> Result<HashMap<ProductId, ProductAjax>> {
+        let mut groups: Vec<(Site, Vec<&str>)> = vec![];
+        for id in product_ids {
+            match groups.iter_mut().find(|(site, _)| *site == id.site()) {
+                Some((_, ids)) => ids.push(id.as_str()),
+                None => groups.push((id.site(), vec![id.as_str()])),
+            }
+        }
+
+        let results = try_join_all(groups.into_iter().map(|(site, ids)| async move {
+            let c = self.c.for_id_site(site);
+            let path = format!("/product/info/ajax?product_id={}", ids.join(","));
+            let ajax_json_str = c.get(&path).await?;
 
-        let json: HashMap<String, ProductAjax> = serde_json::from_str(&ajax_json_str)?;
+            let parsed = ajax::parse_ajax_json(&ajax_json_str);
+            let (json, unknown) = c.parse_context(parsed, &path, None, &ajax_json_str)?;
+            let checked = c.check_unknown_fields(&path, None, unknown);
+            c.parse_context(checked, &path, None, &ajax_json_str)?;
+            Ok::<_, DlsiteError>(json)
+        }))
+        .await?;
 
-        Ok(json)
+        Ok(results.into_iter().flatten().collect())
     }
 
     /// Get product reviews and related informations using 'review api'.
     ///
     /// # Arguments
     /// * `product_id` - Product ID.
-    /// * `limit` - Number of reviews to get.
-    /// * `page` - Page number.
-    /// * `mix_pickup` - Mixes picked up review. To get user genre, this must be true.
-    /// * `order` - Sort order of reviews.
+    /// * `query` - Page, sort order and filters of reviews.
     ///
     /// # Returns
     /// Product reviews and related informations.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlsite::{
+    ///     client::product::review::{ReviewQuery, ReviewSortOrder},
+    ///     DlsiteClient,
+    /// };
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = DlsiteClient::default();
+    ///     let query = ReviewQuery {
+    ///         order: Some(ReviewSortOrder::Helpful),
+    ///         spoiler: Some(false),
+    ///         ..Default::default()
+    ///     };
+    ///     let reviews = client
+    ///         .product()
+    ///         .get_review(&"RJ01014447".parse().unwrap(), &query)
+    ///         .await
+    ///         .unwrap();
+    ///     println!("{:#?}", reviews.review_list);
+    /// }
+    /// ```
     #[tracing::instrument(err, skip_all)]
     pub async fn get_review(
         &self,
-        product_id: &str,
-        limit: u32,
-        page: u32,
-        mix_pickup: bool,
-        order: review::ReviewSortOrder,
+        product_id: &ProductId,
+        query: &review::ReviewQuery,
     ) -> Result<review::ProductReview> {
-        let order_str = match order {
-            review::ReviewSortOrder::New => "regist_d",
-            review::ReviewSortOrder::Top => "top",
-        };
-
-        let path = format!(
-            "/api/review?product_id={}&limit={}&mix_pickup={}&page={}&order={}&locale=ja_JP",
-            product_id, limit, mix_pickup, page, order_str
-        );
-        let json_str = self.c.get(&path).await?;
-        let json: serde_json::Value = serde_json::from_str(&json_str)?;
-
-        if !json["is_success"]
-            .as_bool()
-            .to_parse_error("Failed to parse revire json")?
-        {
-            let message = json["error_msg"]
-                .as_str()
-                .unwrap_or("Failed to get error message");
-            return Err(crate::DlsiteError::Server(format!(
-                "Failed to get review: {}",
-                message
-            )));
-        }
+        let c = self.c.for_id_site(product_id.site());
+        let path = query.to_path(product_id);
+        review::fetch_review(&c, &path, product_id.as_str()).await
+    }
 
-        let json: review::ProductReview = serde_json::from_value(json)?;
-        Ok(json)
+    /// Get all reviews of a product. For how pages are fetched, see [`StreamOptions`]. Picked up
+    /// reviews are not mixed in.
+    ///
+    /// The review count of [`get_ajax`](Self::get_ajax) is requested along with the first page,
+    /// and pages are fetched up to the count or until an empty page. The count includes reviews
+    /// filtered out by the query, so [`PageStream::total`] and [`PageStream::missed`] may count
+    /// more reviews than the query matches.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlsite::{
+    ///     client::{paginate::StreamOptions, product::review::ReviewStreamOptions},
+    ///     DlsiteClient,
+    /// };
+    /// use futures::StreamExt as _;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = DlsiteClient::default();
+    ///     let mut stream = client.product().review_stream(
+    ///         &"RJ01014447".parse().unwrap(),
+    ///         &ReviewStreamOptions::default(),
+    ///         StreamOptions::default(),
+    ///     );
+    ///     while let Some(review) = stream.next().await {
+    ///         println!("{}", review.unwrap().review_title);
+    ///     }
+    /// }
+    /// ```
+    pub fn review_stream(
+        &self,
+        product_id: &ProductId,
+        options: &review::ReviewStreamOptions,
+        stream_options: StreamOptions,
+    ) -> PageStream<review::Review> {
+        let client = self.clone();
+        let id = product_id.clone();
+        let total = move || {
+            let client = client.clone();
+            let id = id.clone();
+            async move {
+                let ajax = client.get_ajax(&id).await?;
+                Ok(ajax
+                    .review_count
+                    .and_then(|count| usize::try_from(count).ok()))
+            }
+        };
+        review::review_stream(
+            self.c.for_id_site(product_id.site()),
+            "product_id",
+            product_id.to_string(),
+            options,
+            stream_options,
+            total,
+        )
     }
 }
@@ -1,12 +1,120 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, convert::Infallible, fmt, future::Future, str::FromStr};
 
+use chrono::{DateTime, FixedOffset, NaiveDateTime};
 use serde::{Deserialize, Deserializer};
+use serde_aux::prelude::{deserialize_number_from_string, deserialize_option_number_from_string};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use strum::{Display, EnumString};
+use url::form_urlencoded;
 
-use crate::interface::genre::Genre;
+use crate::{
+    client::{
+        paginate::{paginate, Page, PageStream, StreamOptions},
+        unknown_field,
+    },
+    error::Result,
+    interface::{genre::Genre, id::ProductId, locale::Locale},
+    utils::{jst, ToParseError as _},
+    DlsiteClient, DlsiteError,
+};
 
+/// Sort order of reviews. The value is the `order` parameter of the review api.
+#[derive(Display, Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum ReviewSortOrder {
+    /// Newest first.
+    #[default]
+    #[strum(serialize = "regist_d")]
     New,
+    /// Oldest first.
+    #[strum(serialize = "regist")]
+    Old,
+    /// Same order as the product page.
+    #[strum(serialize = "top")]
     Top,
+    /// Most helpful first.
+    #[strum(serialize = "good_review_d")]
+    Helpful,
+    /// Highest rating first.
+    #[strum(serialize = "rate_d")]
+    RatingHigh,
+    /// Lowest rating first.
+    #[strum(serialize = "rate")]
+    RatingLow,
+}
+
+/// Options of the review api. Unset options use the defaults of the product page.
+#[derive(Debug, Clone, Default)]
+pub struct ReviewQuery {
+    /// Number of reviews per page. Default is 6.
+    pub limit: Option<u32>,
+    /// Page number, starting from 1.
+    pub page: Option<u32>,
+    /// Mix picked up reviews in. To get reviewer genres, this must be true. Default is true.
+    pub mix_pickup: Option<bool>,
+    pub order: Option<ReviewSortOrder>,
+    /// `Some(false)` excludes reviews with spoilers, and `Some(true)` returns only them.
+    pub spoiler: Option<bool>,
+    /// Return only reviews by users who purchased the product.
+    pub purchased_only: Option<bool>,
+    /// Language of reviews. Default is the locale of the client.
+    pub locale: Option<Locale>,
+}
+
+impl ReviewQuery {
+    /// Convert the query to a path of the review api for reviews of a product.
+    pub fn to_path(&self, product_id: &ProductId) -> String {
+        self.path("product_id", product_id.as_str())
+    }
+
+    /// Convert the query to a path of the review api for reviews written by a reviewer.
+    pub fn to_reviewer_path(&self, reviewer_id: &str) -> String {
+        self.path("reviewer_id", reviewer_id)
+    }
+
+    fn path(&self, key: &str, id: &str) -> String {
+        let mut path = format!(
+            "/api/review?{key}={}&limit={}&mix_pickup={}&page={}&order={}",
+            form_urlencoded::byte_serialize(id.as_bytes()).collect::<String>(),
+            self.limit.unwrap_or(6),
+            self.mix_pickup.unwrap_or(true),
+            self.page.unwrap_or(1),
+            self.order.unwrap_or_default(),
+        );
+        if let Some(spoiler) = self.spoiler {
+            path.push_str(&format!("&spoiler={}", u8::from(spoiler)));
+        }
+        if self.purchased_only == Some(true) {
+            path.push_str("&is_purchased=1");
+        }
+        if let Some(locale) = self.locale {
+            path.push_str(&format!("&locale={locale}"));
+        }
+        path
+    }
+}
+
+/// Options for [`ProductClient::review_stream`](super::ProductClient::review_stream) and
+/// [`ReviewerClient::review_stream`](crate::client::reviewer::ReviewerClient::review_stream).
+#[derive(Debug, Clone)]
+pub struct ReviewStreamOptions {
+    /// Query of each page. `page` is ignored, `mix_pickup` is always false and `limit` is the page
+    /// size (default is 50).
+    pub query: ReviewQuery,
+    /// Stop at the first review registered before this date. Only makes sense with
+    /// [`ReviewSortOrder::New`].
+    pub since: Option<DateTime<FixedOffset>>,
+}
+
+impl Default for ReviewStreamOptions {
+    fn default() -> Self {
+        Self {
+            query: ReviewQuery {
+                limit: Some(50),
+                ..Default::default()
+            },
+            since: None,
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone, Deserialize)]
@@ -18,35 +126,218 @@ pub struct ProductReview {
     pub reviewer_genre_list: Option<Vec<(Genre, i32)>>,
 }
 
-#[derive(Default, Debug, Clone, Deserialize)]
+/// A review of a product.
+#[derive(Debug, Clone, Deserialize)]
 pub struct Review {
     pub member_review_id: String,
-    pub workno: String,
+    pub workno: ProductId,
     pub reviewer_id: String,
-    pub status: String,
-    pub recommend: String,
-    pub spoiler: String,
+    pub status: ReviewStatus,
+    /// Whether the reviewer recommends the product.
+    #[serde(deserialize_with = "deserialize_flag")]
+    pub recommend: bool,
+    /// Whether the review contains spoilers.
+    #[serde(deserialize_with = "deserialize_flag")]
+    pub spoiler: bool,
     pub review_title: String,
     pub review_text: String,
-    pub entry_date: String,
-    pub regist_date: String,
-    pub good_review: String,
-    pub bad_review: String,
+    #[serde(deserialize_with = "deserialize_jst")]
+    pub entry_date: DateTime<FixedOffset>,
+    #[serde(deserialize_with = "deserialize_jst")]
+    pub regist_date: DateTime<FixedOffset>,
+    /// Number of users who found the review helpful.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub good_review: i32,
+    /// Number of users who didn't find the review helpful.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub bad_review: i32,
     pub circle_id: Option<String>,
     pub nick_name: Option<String>,
-    pub popularity: Option<String>,
-    pub rate: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    pub popularity: Option<i32>,
+    /// Rating given by the reviewer, from 1 to 5.
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    pub rate: Option<i32>,
     pub circle_name: Option<String>,
     pub top_sort_key: Option<String>,
     pub reviewer_status: String,
-    pub is_purchased: String,
-    pub rate_num: String,
-    pub reviewer_rank: String,
+    /// Whether the reviewer purchased the product.
+    #[serde(deserialize_with = "deserialize_flag")]
+    pub is_purchased: bool,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub rate_num: i32,
+    pub reviewer_rank: ReviewerRank,
     #[serde(default)]
     #[serde(deserialize_with = "deserialize_genre")]
     pub genre: Vec<Genre>,
 }
 
+/// Status of a review.
+#[derive(Display, EnumString, Debug, Clone, PartialEq, Eq, DeserializeFromStr)]
+pub enum ReviewStatus {
+    /// Published on the product page.
+    #[strum(serialize = "1")]
+    Published,
+    /// Raw value which this crate doesn't know.
+    #[strum(default)]
+    Unknown(String),
+}
+
+/// Rank of a reviewer in the reviewer ranking.
+#[derive(Debug, Clone, PartialEq, Eq, SerializeDisplay, DeserializeFromStr)]
+pub enum ReviewerRank {
+    /// Not ranked (`""` or `"0"`).
+    Unranked,
+    Ranked(u32),
+    /// Raw value which this crate doesn't know.
+    Unknown(String),
+}
+
+impl FromStr for ReviewerRank {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s.trim() {
+            "" | "0" => ReviewerRank::Unranked,
+            s => match s.parse() {
+                Ok(rank) => ReviewerRank::Ranked(rank),
+                Err(_) => ReviewerRank::Unknown(s.to_string()),
+            },
+        })
+    }
+}
+
+impl fmt::Display for ReviewerRank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewerRank::Unranked => f.write_str("0"),
+            ReviewerRank::Ranked(rank) => write!(f, "{rank}"),
+            ReviewerRank::Unknown(s) => f.write_str(s),
+        }
+    }
+}
+
+/// Deserialize a date like `2023-02-18 12:34:56` in JST.
+fn deserialize_jst<'de, D>(deserializer: D) -> std::result::Result<DateTime<FixedOffset>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S")
+        .map_err(serde::de::Error::custom)?
+        .and_local_timezone(jst())
+        .single()
+        .ok_or_else(|| serde::de::Error::custom(format!("Invalid date: {s}")))
+}
+
+/// Deserialize a flag which is `"0"`/`"1"`, a number or a bool.
+fn deserialize_flag<'de, D>(deserializer: D) -> std::result::Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Number(i64),
+        String(String),
+    }
+    match Flag::deserialize(deserializer)? {
+        Flag::Bool(b) => Ok(b),
+        Flag::Number(n) => Ok(n != 0),
+        Flag::String(s) => match s.as_str() {
+            "" | "0" => Ok(false),
+            "1" => Ok(true),
+            _ => Err(serde::de::Error::custom(format!("Invalid flag: {s}"))),
+        },
+    }
+}
+
+/// Fetch a page of the review api and parse it.
+pub(crate) async fn fetch_review(c: &DlsiteClient, path: &str, id: &str) -> Result<ProductReview> {
+    let json_str = c.get(path).await?;
+
+    let parsed = parse_review_json(&json_str);
+    let (review, unknown) = c.parse_context(parsed, path, Some(id), &json_str)?;
+    let checked = c.check_unknown_fields(path, Some(id), unknown);
+    c.parse_context(checked, path, Some(id), &json_str)?;
+    Ok(review)
+}
+
+/// Stream reviews of all pages of the review api, where `key` is the parameter of `id` like
+/// `product_id`.
+///
+/// The review api doesn't report the total count, so `total` is requested along with the first
+/// page to get it from elsewhere. Pages are fetched until an empty page if it returns `None`.
+pub(crate) fn review_stream<T, TFut>(
+    c: DlsiteClient,
+    key: &'static str,
+    id: String,
+    options: &ReviewStreamOptions,
+    stream_options: StreamOptions,
+    total: T,
+) -> PageStream<Review>
+where
+    T: Fn() -> TFut + Clone + Send + Sync + 'static,
+    TFut: Future<Output = Result<Option<usize>>> + Send,
+{
+    let per_page = options.query.limit.unwrap_or(50).max(1);
+    let query = options.query.clone();
+    let fetch = move |page| {
+        let c = c.clone();
+        let id = id.clone();
+        let path = ReviewQuery {
+            limit: Some(per_page),
+            page: Some(page),
+            mix_pickup: Some(false),
+            ..query.clone()
+        }
+        .path(key, &id);
+        let total = total.clone();
+        async move {
+            let (review, total) = if page == 1 {
+                futures::try_join!(fetch_review(&c, &path, &id), total())?
+            } else {
+                (fetch_review(&c, &path, &id).await?, None)
+            };
+            Ok(Page {
+                items: review.review_list,
+                total,
+            })
+        }
+    };
+
+    let stream = paginate(stream_options, Some(per_page as usize), fetch, |review| {
+        review.member_review_id.clone()
+    });
+    match options.since {
+        Some(since) => stream.stop_when(move |review| review.regist_date < since),
+        None => stream,
+    }
+}
+
+/// Parse json returned by the review api.
+///
+/// Returns the review and paths of unknown fields.
+pub(crate) fn parse_review_json(json_str: &str) -> Result<(ProductReview, Vec<String>)> {
+    let json: serde_json::Value = serde_json::from_str(json_str)?;
+
+    if !json["is_success"]
+        .as_bool()
+        .to_field_error("is_success", "Failed to parse review json")?
+    {
+        let message = json["error_msg"]
+            .as_str()
+            .unwrap_or("Failed to get error message");
+        return Err(DlsiteError::Server(format!(
+            "Failed to get review: {}",
+            message
+        )));
+    }
+
+    unknown_field::deserialize(json)
+}
+
 fn deserialize_genre<'de, D>(deserializer: D) -> std::result::Result<Vec<Genre>, D::Error>
 where
     D: Deserializer<'de>,
@@ -97,3 +388,159 @@ where
         Ok(None)
     }
 }
+
+/// Responses of the review api for tests.
+#[cfg(test)]
+pub(crate) mod test_json {
+    /// A review in a response, with the raw values of the json.
+    pub(crate) struct TestReview<'a> {
+        pub member_review_id: &'a str,
+        pub workno: &'a str,
+        pub reviewer_id: &'a str,
+        pub entry_date: &'a str,
+        pub regist_date: &'a str,
+        pub good_review: &'a str,
+        pub bad_review: &'a str,
+        pub nick_name: Option<&'a str>,
+        pub popularity: Option<&'a str>,
+        pub rate: &'a str,
+        pub rate_num: &'a str,
+        pub reviewer_rank: &'a str,
+    }
+
+    impl<'a> TestReview<'a> {
+        /// A review of RJ01014447 posted at `date`.
+        pub(crate) fn new(id: &'a str, date: &'a str) -> Self {
+            Self {
+                member_review_id: id,
+                workno: "RJ01014447",
+                reviewer_id: "1",
+                entry_date: date,
+                regist_date: date,
+                good_review: "0",
+                bad_review: "0",
+                nick_name: None,
+                popularity: None,
+                rate: "5",
+                rate_num: "5",
+                reviewer_rank: "0",
+            }
+        }
+
+        fn to_json(&self) -> serde_json::Value {
+            serde_json::json!({
+                "member_review_id": self.member_review_id, "workno": self.workno,
+                "reviewer_id": self.reviewer_id, "status": "1", "recommend": "1",
+                "spoiler": "0", "review_title": format!("Review {}", self.member_review_id),
+                "review_text": "", "entry_date": self.entry_date,
+                "regist_date": self.regist_date, "good_review": self.good_review,
+                "bad_review": self.bad_review, "circle_id": null, "nick_name": self.nick_name,
+                "popularity": self.popularity, "rate": self.rate, "circle_name": null,
+                "top_sort_key": null, "reviewer_status": "1", "is_purchased": "1",
+                "rate_num": self.rate_num, "reviewer_rank": self.reviewer_rank, "genre": null
+            })
+        }
+    }
+
+    /// A successful response with `reviews`.
+    pub(crate) fn review_page(reviews: &[TestReview]) -> String {
+        serde_json::json!({
+            "is_success": true, "error_msg": "", "reviewer_genre_list": null,
+            "review_list": reviews.iter().map(TestReview::to_json).collect::<Vec<_>>()
+        })
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, TimeZone as _};
+
+    use super::{
+        parse_review_json,
+        test_json::{review_page, TestReview},
+        ReviewQuery, ReviewSortOrder, ReviewStatus, ReviewerRank,
+    };
+    use crate::interface::locale::Locale;
+
+    #[test]
+    fn review_query() {
+        let id = "RJ01014447".parse().unwrap();
+        assert_eq!(
+            ReviewQuery::default().to_path(&id),
+            "/api/review?product_id=RJ01014447&limit=6&mix_pickup=true&page=1&order=regist_d"
+        );
+        assert_eq!(
+            ReviewQuery {
+                limit: Some(20),
+                page: Some(3),
+                mix_pickup: Some(false),
+                order: Some(ReviewSortOrder::Helpful),
+                spoiler: Some(false),
+                purchased_only: Some(true),
+                locale: Some(Locale::English),
+            }
+            .to_path(&id),
+            "/api/review?product_id=RJ01014447&limit=20&mix_pickup=false&page=3&order=good_review_d&spoiler=0&is_purchased=1&locale=en_US"
+        );
+    }
+
+    #[test]
+    fn typed_review() {
+        let json = review_page(&[TestReview {
+            entry_date: "2023-02-18 12:34:56",
+            good_review: "12",
+            popularity: Some("3"),
+            rate: "",
+            reviewer_rank: "",
+            ..TestReview::new("123", "2023-02-19 00:00:00")
+        }]);
+        let (review, _) = parse_review_json(&json).unwrap();
+        let review = &review.review_list[0];
+
+        assert_eq!(review.workno, "RJ01014447");
+        assert_eq!(review.status, ReviewStatus::Published);
+        assert!(review.recommend);
+        assert!(!review.spoiler);
+        assert!(review.is_purchased);
+        assert_eq!(
+            review.entry_date,
+            FixedOffset::east_opt(9 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2023, 2, 18, 12, 34, 56)
+                .unwrap()
+        );
+        assert_eq!(review.good_review, 12);
+        assert_eq!(review.bad_review, 0);
+        assert_eq!(review.popularity, Some(3));
+        assert_eq!(review.rate, None);
+        assert_eq!(review.rate_num, 5);
+        assert_eq!(review.reviewer_rank, ReviewerRank::Unranked);
+        assert!(review.genre.is_empty());
+    }
+
+    #[test]
+    fn fallback_to_raw_value() {
+        assert_eq!(
+            "9".parse::<ReviewStatus>().unwrap(),
+            ReviewStatus::Unknown("9".to_string())
+        );
+        assert_eq!("3".parse(), Ok(ReviewerRank::Ranked(3)));
+        assert_eq!(
+            "gold".parse(),
+            Ok(ReviewerRank::Unknown("gold".to_string()))
+        );
+    }
+
+    #[test]
+    fn reviewer_rank_round_trip() {
+        for rank in [
+            ReviewerRank::Unranked,
+            ReviewerRank::Ranked(3),
+            ReviewerRank::Unknown("gold".to_string()),
+        ] {
+            let json = serde_json::to_string(&rank).unwrap();
+            assert_eq!(serde_json::from_str::<ReviewerRank>(&json).unwrap(), rank);
+        }
+    }
+}
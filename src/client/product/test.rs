@@ -2,16 +2,33 @@ use anyhow::Context;
 use chrono::NaiveDate;
 use test_case::test_case;
 
+use futures::StreamExt as _;
+
 use crate::{
+    client::{
+        cassette::{test_client, test_client_builder},
+        paginate::StreamOptions,
+        product::review::{
+            test_json::{review_page, TestReview},
+            Review, ReviewQuery, ReviewSortOrder, ReviewStreamOptions,
+        },
+        stub::{StubResponse, StubServer},
+        ParseMode,
+    },
     interface::genre::Genre,
+    interface::locale::Locale,
     interface::product::{AgeCategory, WorkType},
-    DlsiteClient,
+    DlsiteClient, DlsiteError,
 };
 
 #[tokio::test]
 async fn get_product_1_content() {
-    let client = DlsiteClient::default();
-    let res = client.product().get_all("RJ403038").await.unwrap();
+    let client = test_client("product/get_product_1_content");
+    let res = client
+        .product()
+        .get_all(&"RJ403038".parse().unwrap())
+        .await
+        .unwrap();
 
     assert_eq!(res.id, "RJ403038".to_string());
     assert_eq!(
@@ -38,10 +55,10 @@ async fn get_product_1_content() {
 
 #[tokio::test]
 async fn get_product_2() {
-    let client = DlsiteClient::default();
+    let client = test_client("product/get_product_2");
     let res = client
         .product()
-        .get_all("RJ01017217")
+        .get_all(&"RJ01017217".parse().unwrap())
         .await
         .context("Failed to get product info");
     let res = res.unwrap();
@@ -77,6 +94,393 @@ async fn get_product_2() {
 #[test_case("VJ01000513"; "soft")]
 #[tokio::test]
 async fn get_product_success(id: &str) {
-    let client = DlsiteClient::default();
-    client.product().get_all(id).await.unwrap();
+    let client = test_client(&format!("product/get_product_success/{id}"));
+    client
+        .product()
+        .get_all(&id.parse().unwrap())
+        .await
+        .unwrap();
+}
+
+#[test_case(Locale::English; "en")]
+#[test_case(Locale::ChineseSimplified; "zh_cn")]
+#[test_case(Locale::ChineseTraditional; "zh_tw")]
+#[test_case(Locale::Korean; "ko")]
+#[tokio::test]
+async fn get_product_html_localized(locale: Locale) {
+    let client = test_client_builder(&format!("product/get_product_html_localized/{locale}"))
+        .locale(locale)
+        .build()
+        .unwrap();
+    let res = client
+        .product()
+        .get_html(&"RJ403038".parse().unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        res.released_at,
+        NaiveDate::from_ymd_opt(2022, 7, 17).unwrap()
+    );
+    assert_eq!(res.circle_id, "RG62982");
+    assert_eq!(res.age_rating, Some(AgeCategory::General));
+    assert!(res.people.voice_actor.is_some());
+    assert!(res.genre.iter().any(|g| g.id == "497"));
+}
+
+#[tokio::test]
+async fn get_ajax_multiple_sites() {
+    let server = StubServer::start(vec![StubResponse::ok("[]"); 2]).await;
+    let client = DlsiteClient::new(&format!("{}/maniax", server.url()));
+    let ids = ["RJ01000001", "VJ01000002", "RJ01000003"].map(|id| id.parse().unwrap());
+
+    client.product().get_ajax_multiple(&ids).await.unwrap();
+    let mut paths = server.paths();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![
+            "/maniax/product/info/ajax?product_id=RJ01000001,RJ01000003&locale=ja_JP",
+            "/pro/product/info/ajax?product_id=VJ01000002&locale=ja_JP"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn get_product_not_found() {
+    let server = StubServer::start(vec![StubResponse::status(404), StubResponse::ok("[]")]).await;
+    let client = DlsiteClient::new(&server.url());
+
+    assert!(matches!(
+        client.product().get_html(&"RJ00000000".parse().unwrap()).await,
+        Err(DlsiteError::NotFound { id }) if id == "RJ00000000"
+    ));
+    assert!(matches!(
+        client.product().get_ajax(&"RJ00000000".parse().unwrap()).await,
+        Err(DlsiteError::NotFound { id }) if id == "RJ00000000"
+    ));
+}
+
+const ENGLISH_PRODUCT_HTML: &str = r##"<html><body>
+<table id="work_maker"><tr><td><span class="maker_name">
+  <a href="https://www.dlsite.com/maniax/circle/profile/=/maker_id/RG24350.html">Circle</a>
+</span></td></tr></table>
+<table id="work_outline">
+  <tr><th>Release date</th><td><a href="#">02/18/2023</a></td></tr>
+  <tr><th>Author</th><td><a href="#">Author A</a></td></tr>
+  <tr><th>Voice Actor</th><td><a href="#">Voice A</a> / <a href="#">Voice B</a></td></tr>
+  <tr><th>Age</th><td><div class="work_genre"><span>All Ages</span></div></td></tr>
+  <tr><th>Product format</th><td><div class="work_genre"><a href="#"><span>Voice / ASMR</span></a></div></td></tr>
+  <tr><th>Genre</th><td><div class="main_genre">
+    <a href="https://www.dlsite.com/maniax/fsr/=/genre/497/from/work.genre">ASMR</a>
+  </div></td></tr>
+  <tr><th>File size</th><td><div>Total&nbsp;1.2GB</div></td></tr>
+</table>
+</body></html>"##;
+
+#[tokio::test]
+async fn get_product_html_english() {
+    let server = StubServer::start(vec![StubResponse::ok(ENGLISH_PRODUCT_HTML)]).await;
+    let client = DlsiteClient::builder()
+        .base_url(&server.url())
+        .locale(Locale::English)
+        .build()
+        .unwrap();
+
+    let res = client
+        .product()
+        .get_html(&"RJ01014447".parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(
+        res.released_at,
+        NaiveDate::from_ymd_opt(2023, 2, 18).unwrap()
+    );
+    assert_eq!(res.age_rating, Some(AgeCategory::General));
+    assert_eq!(res.circle_id, "RG24350");
+    assert_eq!(res.product_format, vec!["Voice / ASMR"]);
+    assert_eq!(res.file_size.as_deref(), Some("1.2GB"));
+    assert_eq!(
+        res.genre,
+        vec![Genre {
+            name: "ASMR".to_string(),
+            id: "497".to_string()
+        }]
+    );
+    assert_eq!(res.people.author, Some(vec!["Author A".to_string()]));
+    assert_eq!(
+        res.people.voice_actor,
+        Some(vec!["Voice A".to_string(), "Voice B".to_string()])
+    );
+    assert_eq!(
+        server.paths(),
+        vec!["/work/=/product_id/RJ01014447?locale=en_US"]
+    );
+}
+
+#[tokio::test]
+async fn get_product_html_unknown_row() {
+    let html = ENGLISH_PRODUCT_HTML.replace(
+        "</table>\n</body>",
+        r#"<tr><th>New row</th><td>Text <a href="https://example.com/a">link</a></td></tr></table></body>"#,
+    );
+    let server = StubServer::start(vec![StubResponse::ok(&html); 2]).await;
+    let id = "RJ01014447".parse().unwrap();
+
+    let client = DlsiteClient::builder()
+        .base_url(&server.url())
+        .locale(Locale::English)
+        .build()
+        .unwrap();
+    let res = client.product().get_html(&id).await.unwrap();
+    assert_eq!(
+        res.extra,
+        vec![(
+            "New row".to_string(),
+            "Text link".to_string(),
+            vec!["https://example.com/a".to_string()]
+        )]
+    );
+
+    let client = DlsiteClient::builder()
+        .base_url(&server.url())
+        .locale(Locale::English)
+        .parse_mode(ParseMode::Strict)
+        .build()
+        .unwrap();
+    let Err(DlsiteError::Parse(e)) = client.product().get_html(&id).await else {
+        panic!("Expected parse error");
+    };
+    assert_eq!(e.field.as_deref(), Some("New row"));
+}
+
+#[test_case(ReviewSortOrder::New; "new")]
+#[test_case(ReviewSortOrder::Old; "old")]
+#[test_case(ReviewSortOrder::Helpful; "helpful")]
+#[test_case(ReviewSortOrder::RatingHigh; "rating_high")]
+#[test_case(ReviewSortOrder::RatingLow; "rating_low")]
+#[tokio::test]
+async fn get_review_order(order: ReviewSortOrder) {
+    let client = test_client(&format!("product/get_review_order/{order}"));
+    let query = ReviewQuery {
+        limit: Some(20),
+        mix_pickup: Some(false),
+        order: Some(order),
+        ..Default::default()
+    };
+    let reviews = client
+        .product()
+        .get_review(&"RJ01014447".parse().unwrap(), &query)
+        .await
+        .unwrap()
+        .review_list;
+    assert!(reviews.len() > 1);
+
+    // An ignored order would return the newest reviews first instead of failing.
+    let sorted = |key: fn(&Review) -> Option<i64>, descending: bool| {
+        let keys: Vec<_> = reviews.iter().filter_map(key).collect();
+        keys.windows(2).all(|w| {
+            if descending {
+                w[0] >= w[1]
+            } else {
+                w[0] <= w[1]
+            }
+        })
+    };
+    let date = |r: &Review| Some(r.regist_date.timestamp());
+    match order {
+        ReviewSortOrder::New => assert!(sorted(date, true)),
+        ReviewSortOrder::Old => assert!(sorted(date, false) && !sorted(date, true)),
+        ReviewSortOrder::Helpful => assert!(sorted(|r| Some(r.good_review.into()), true)),
+        ReviewSortOrder::RatingHigh => assert!(sorted(|r| r.rate.map(Into::into), true)),
+        ReviewSortOrder::RatingLow => assert!(sorted(|r| r.rate.map(Into::into), false)),
+        ReviewSortOrder::Top => unreachable!(),
+    }
+}
+
+#[test_case("no_spoiler", Some(false), None)]
+#[test_case("spoiler_only", Some(true), None)]
+#[test_case("purchased_only", None, Some(true))]
+#[tokio::test]
+async fn get_review_filter(name: &str, spoiler: Option<bool>, purchased_only: Option<bool>) {
+    let client = test_client(&format!("product/get_review_filter/{name}"));
+    let query = ReviewQuery {
+        limit: Some(50),
+        mix_pickup: Some(false),
+        spoiler,
+        purchased_only,
+        ..Default::default()
+    };
+    let reviews = client
+        .product()
+        .get_review(&"RJ01014447".parse().unwrap(), &query)
+        .await
+        .unwrap()
+        .review_list;
+
+    // Both spoiler filters returning matching reviews shows that the parameter isn't ignored.
+    assert!(!reviews.is_empty());
+    if let Some(spoiler) = spoiler {
+        assert!(reviews.iter().all(|r| r.spoiler == spoiler));
+    }
+    if purchased_only == Some(true) {
+        assert!(reviews.iter().all(|r| r.is_purchased));
+    }
+}
+
+/// Response of the ajax api with only the required fields of [`ProductAjax`].
+///
+/// [`ProductAjax`]: crate::client::product::ajax::ProductAjax
+fn ajax_response(id: &str, review_count: &str) -> StubResponse {
+    let mut product: serde_json::Value = serde_json::from_str(&format!(
+        r#"{{"site_id": "maniax", "site_id_touch": "maniaxtouch", "maker_id": "RG24350",
+        "work_name": "Title", "work_name_masked": "Title", "work_type": "SOU", "price": 0,
+        "price_str": "0", "down_url": "", "work_image": "", "regist_date": "2023-02-18 16:00:00",
+        "default_point_str": "0", "options": "", "upgrade_min_price": 0, "limit_stock": 0,
+        "timesale_stock": 0, "official_price": 0, "age_category": 1, "affiliate_deny": 0,
+        "dl_format": 0, "wishlist_count": 0, "price_without_tax": 0, "default_point_rate": 0,
+        "default_point": 0, "on_sale": 1, "locale_price": {{}}, "currency_price": {{}},
+        "custom_genres": [], "locale_price_str": {{}}, "rank": [], "rate_count_detail": [],
+        "bonuses": [], "limited_free_terms": [], "gift": [], "work_rentals": [],
+        "dl_count": null, "review_count": "{review_count}",
+        "translation_info": {{"production_trade_price_rate": 0, "is_volunteer": false,
+            "translation_bonus_langs": [], "is_translation_bonus_child": false,
+            "is_translation_agree": false, "is_original": false, "is_child": false,
+            "is_parent": false, "child_worknos": []}}}}"#
+    ))
+    .unwrap();
+    for flag in [
+        "dlsiteplay_work",
+        "is_discount",
+        "is_pointup",
+        "is_rental",
+        "is_ana",
+        "is_sale",
+        "is_title_completed",
+        "is_limit_work",
+        "is_sold_out",
+        "is_reserve_work",
+        "is_reservable",
+        "is_timesale",
+        "is_free",
+        "is_oly",
+        "is_led",
+        "is_noreduction",
+        "is_wcc",
+        "is_pack_work",
+    ] {
+        product[flag] = false.into();
+    }
+    StubResponse::ok(&serde_json::json!({ id: product }).to_string()).path("/product/info/ajax")
+}
+
+#[tokio::test]
+async fn review_stream() {
+    let server = StubServer::start(vec![
+        ajax_response("RJ01014447", "3"),
+        StubResponse::ok(&review_page(&[
+            TestReview::new("3", "2023-03-03 00:00:00"),
+            TestReview::new("2", "2023-03-02 00:00:00"),
+        ])),
+        StubResponse::ok(&review_page(&[TestReview::new("1", "2023-03-01 00:00:00")])),
+    ])
+    .await;
+    let client = DlsiteClient::builder()
+        .base_url(&server.url())
+        .build()
+        .unwrap();
+    let options = ReviewStreamOptions {
+        query: ReviewQuery {
+            limit: Some(2),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let ids: Vec<_> = client
+        .product()
+        .review_stream(
+            &"RJ01014447".parse().unwrap(),
+            &options,
+            StreamOptions::default(),
+        )
+        .map(|review| review.unwrap().member_review_id)
+        .collect()
+        .await;
+    assert_eq!(ids, vec!["3", "2", "1"]);
+    // The ajax api and two pages, without an empty page after the review count.
+    assert_eq!(server.hits(), 3);
+    assert!(server
+        .paths()
+        .iter()
+        .any(|path| path.contains("limit=2&mix_pickup=false&page=2&order=regist_d")));
+}
+
+#[tokio::test]
+async fn review_stream_without_count() {
+    let server = StubServer::start(vec![
+        ajax_response("RJ01014447", ""),
+        StubResponse::ok(&review_page(&[
+            TestReview::new("3", "2023-03-03 00:00:00"),
+            TestReview::new("2", "2023-03-02 00:00:00"),
+        ])),
+        StubResponse::ok(&review_page(&[])),
+    ])
+    .await;
+    let client = DlsiteClient::builder()
+        .base_url(&server.url())
+        .build()
+        .unwrap();
+    let options = ReviewStreamOptions {
+        query: ReviewQuery {
+            limit: Some(2),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let stream = client.product().review_stream(
+        &"RJ01014447".parse().unwrap(),
+        &options,
+        StreamOptions::default(),
+    );
+    let ids: Vec<_> = stream
+        .map(|review| review.unwrap().member_review_id)
+        .collect()
+        .await;
+    assert_eq!(ids, vec!["3", "2"]);
+    assert_eq!(server.hits(), 3);
+}
+
+#[tokio::test]
+async fn review_stream_since() {
+    let server = StubServer::start(vec![
+        ajax_response("RJ01014447", "10"),
+        StubResponse::ok(&review_page(&[
+            TestReview::new("3", "2023-03-03 00:00:00"),
+            TestReview::new("2", "2023-03-02 00:00:00"),
+        ])),
+    ])
+    .await;
+    let client = DlsiteClient::builder()
+        .base_url(&server.url())
+        .build()
+        .unwrap();
+    let options = ReviewStreamOptions {
+        query: ReviewQuery {
+            limit: Some(2),
+            ..Default::default()
+        },
+        since: Some("2023-03-02T12:00:00+09:00".parse().unwrap()),
+    };
+    let ids: Vec<_> = client
+        .product()
+        .review_stream(
+            &"RJ01014447".parse().unwrap(),
+            &options,
+            StreamOptions::default(),
+        )
+        .map(|review| review.unwrap().member_review_id)
+        .collect()
+        .await;
+    assert_eq!(ids, vec!["3"]);
+    assert_eq!(server.hits(), 2);
 }
@@ -4,7 +4,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_with::{formats::PreferOne, serde_as, DefaultOnError, OneOrMany};
 
-use crate::interface::product::{AgeCategory, FileType, WorkCategory, WorkType};
+use crate::interface::{
+    product::{AgeCategory, FileType, WorkCategory, WorkType},
+    site::Site,
+};
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
@@ -375,6 +378,13 @@ pub struct ProductApiContent {
     pub limit_sold_dl_count: i32,
 }
 
+impl ProductApiContent {
+    /// Site of the product, parsed from `site_id`. `None` if the site is unknown to this crate.
+    pub fn site(&self) -> Option<Site> {
+        self.site_id.parse().ok()
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "unknown-field-error", serde(deny_unknown_fields))]
 pub struct SpecifiedVolumeSet {
@@ -4,7 +4,9 @@ pub mod interface;
 #[cfg(test)]
 mod test;
 
-use crate::{error::Result, DlsiteClient, DlsiteError};
+use crate::{
+    client::unknown_field, error::Result, interface::id::ProductId, DlsiteClient, DlsiteError,
+};
 
 use self::interface::ProductApiContent;
 
@@ -12,11 +14,11 @@ use self::interface::ProductApiContent;
 ///
 /// For difference about "scraping" and "api" method, see [`super::product::ProductClient`].
 #[derive(Clone, Debug)]
-pub struct ProductApiClient<'a> {
-    pub(crate) c: &'a DlsiteClient,
+pub struct ProductApiClient {
+    pub(crate) c: DlsiteClient,
 }
 
-impl<'a> ProductApiClient<'a> {
+impl ProductApiClient {
     /// Get product detail using api.
     ///
     /// # Arguments
@@ -28,6 +30,9 @@ impl<'a> ProductApiClient<'a> {
     /// # Note
     /// This api does not return dl count.
     ///
+    /// # Errors
+    /// Returns [`DlsiteError::NotFound`] if the product does not exist.
+    ///
     /// # Example
     /// ```
     /// use dlsite::DlsiteClient;
@@ -35,35 +40,23 @@ impl<'a> ProductApiClient<'a> {
     /// #[tokio::main]
     /// async fn main() {
     ///     let client = DlsiteClient::default();
-    ///     let product = client.product_api().get("RJ01014447").await.unwrap();
+    ///     let product = client.product_api().get(&"RJ01014447".parse().unwrap()).await.unwrap();
     ///     assert_eq!(product.creators.unwrap().voice_by.unwrap()[0].name, "佐倉綾音");
     /// }
     /// ```
-    pub async fn get(&self, id: &str) -> Result<ProductApiContent> {
-        let json = self
-            .c
-            .get(&format!("/api/=/product.json?workno={}", id))
-            .await?;
-        let jd = &mut serde_json::Deserializer::from_str(&json);
-        #[cfg(feature = "unknown-field-log")]
-        let result: std::result::Result<Vec<ProductApiContent>, _> = serde_ignored::deserialize(
-            jd,
-            |path| {
-                tracing::error!("Ignored path: '{}' for '{id}'. Please report this to https://github.com/ozonezone/dlsite-rs", path.to_string());
-            },
-        );
-        #[cfg(not(feature = "unknown-field-log"))]
-        let result: std::result::Result<Vec<ProductApiContent>, _> =
-            serde_path_to_error::deserialize(jd);
-        match result {
-            Ok(result) => {
-                let Some(json) = result.into_iter().next() else {
-                    return Err(DlsiteError::Parse("No product found".to_string()));
-                };
+    pub async fn get(&self, id: &ProductId) -> Result<ProductApiContent> {
+        let c = self.c.for_id_site(id.site());
+        let path = format!("/api/=/product.json?workno={}", id);
+        let json = c.get(&path).await?;
+        let parsed = unknown_field::from_json::<Vec<ProductApiContent>>(&json);
+        let (result, unknown) = c.parse_context(parsed, &path, Some(id.as_str()), &json)?;
+        let checked = c.check_unknown_fields(&path, Some(id.as_str()), unknown);
+        c.parse_context(checked, &path, Some(id.as_str()), &json)?;
+        let Some(json) = result.into_iter().next() else {
+            return Err(DlsiteError::NotFound { id: id.to_string() });
+        };
+        c.check_site(id.as_str(), &json.site_id);
 
-                Ok(json)
-            }
-            Err(e) => Err(DlsiteError::Parse(format!("Failed to parse json: {}", e))),
-        }
+        Ok(json)
     }
 }
@@ -1,17 +1,20 @@
-use anyhow::Context;
-use rand::Rng;
-
 use super::interface::GenreApi;
 use crate::{
+    client::cassette::test_client,
     interface::product::{AgeCategory, WorkType},
     DlsiteClient,
 };
+use anyhow::Context;
 use test_case::test_case;
 
 #[tokio::test]
 async fn get_product_api_1_content() {
-    let client = DlsiteClient::default();
-    let res = client.product_api().get("RJ403038").await.unwrap();
+    let client = test_client("product_api/get_product_api_1_content");
+    let res = client
+        .product_api()
+        .get(&"RJ403038".parse().unwrap())
+        .await
+        .unwrap();
 
     assert_eq!(res.workno, "RJ403038");
     assert_eq!(
@@ -25,10 +28,10 @@ async fn get_product_api_1_content() {
 
 #[tokio::test]
 async fn get_product_api_2() {
-    let client = DlsiteClient::default();
+    let client = test_client("product_api/get_product_api_2");
     let res = client
         .product_api()
-        .get("RJ01017217")
+        .get(&"RJ01017217".parse().unwrap())
         .await
         .context("Failed to get product info");
     let res = res.unwrap();
@@ -61,8 +64,12 @@ async fn get_product_api_2() {
 #[test_case("RJ01060083"; "normal")]
 #[tokio::test]
 async fn get_product_api_success(id: &str) {
-    let client = DlsiteClient::default();
-    client.product_api().get(id).await.unwrap();
+    let client = test_client(&format!("product_api/get_product_api_success/{id}"));
+    client
+        .product_api()
+        .get(&id.parse().unwrap())
+        .await
+        .unwrap();
 }
 
 #[tokio::test]
@@ -70,38 +77,30 @@ async fn get_product_api_env() {
     if let Some(id) = std::option_env!("PRODUCT_TEST_ID") {
         println!("Testing for {}", id);
         let client = DlsiteClient::default();
-        client.product_api().get(id).await.unwrap();
+        client
+            .product_api()
+            .get(&id.parse().unwrap())
+            .await
+            .unwrap();
     }
 }
 
-#[test_case("RJ"; "rj")]
-// #[test_case("VJ"; "vj")]
 #[tokio::test]
-async fn get_product_api_rand(product_type: &str) {
-    let mut rng = rand::rng();
-    let mut i = 0;
-    loop {
-        let id = rng.random_range(100000..1000000);
-        let id = if id >= 1000000 {
-            format!("{}0{}", product_type, id)
-        } else {
-            format!("{}{}", product_type, id)
-        };
-        let client = DlsiteClient::default();
-        let pre_req = client
-            .get(&format!("/api/=/product.json?workno={}", id))
+async fn get_product_api_many() {
+    // Products from old to new IDs. Fixed rather than random so that they match the cassette.
+    let client = test_client("product_api/get_product_api_many");
+    for id in [
+        "RJ291224",
+        "RJ403038",
+        "RJ01014447",
+        "RJ01017217",
+        "RJ01060083",
+    ] {
+        println!("Testing for {}", id);
+        client
+            .product_api()
+            .get(&id.parse().unwrap())
             .await
             .unwrap();
-        if pre_req.trim() == "[]" {
-            println!("Testing for {}: Invalid id", id);
-            continue;
-        }
-        println!("Testing for {}", id);
-        client.product_api().get(&id).await.unwrap();
-
-        i += 1;
-        if i >= 5 {
-            break;
-        }
     }
 }
@@ -0,0 +1,313 @@
+//! Interfaces related to ranking only. For more information, see [`RankingClient`].
+
+use scraper::{ElementRef, Html, Selector};
+use strum::Display;
+
+use super::DlsiteClient;
+use crate::{
+    error::{ParseError, Result},
+    interface::{
+        id::{MakerId, ProductId},
+        url::DlsiteUrl,
+    },
+    utils::ToParseError as _,
+};
+
+/// Client to get rankings on DLsite.
+///
+/// Rankings are per site, so use [`DlsiteClient::site`] to get rankings of other sites than the
+/// default one.
+#[derive(Clone, Debug)]
+pub struct RankingClient {
+    pub(crate) c: DlsiteClient,
+}
+
+/// Term of a ranking.
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[strum(serialize_all = "snake_case")]
+pub enum RankingTerm {
+    Day,
+    Week,
+    Month,
+    Year,
+    Total,
+}
+
+/// Category of a ranking. The value is the `category` parameter of the ranking page.
+#[derive(Display, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[strum(serialize_all = "snake_case")]
+pub enum RankingCategory {
+    /// All works.
+    #[default]
+    All,
+    /// Voice, ASMR and music.
+    Voice,
+    Game,
+    Comic,
+    /// CG and illustrations.
+    Cg,
+    Novel,
+    Video,
+    /// Tools, accessories and others.
+    Etc,
+}
+
+/// Movement of a rank from the previous ranking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum RankMovement {
+    Up,
+    Down,
+    Stay,
+    /// Newly ranked in.
+    New,
+}
+
+/// An entry of a ranking.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RankingEntry {
+    pub rank: i32,
+    pub id: ProductId,
+    pub title: String,
+    pub circle_name: String,
+    pub circle_id: Option<MakerId>,
+    /// Current price. `None` if the page doesn't show it.
+    pub price: Option<i32>,
+    /// `None` if the page doesn't show it.
+    pub movement: Option<RankMovement>,
+}
+
+impl RankingClient {
+    /// Get a ranking. Entries are ordered by rank.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlsite::{
+    ///     client::ranking::{RankingCategory, RankingTerm},
+    ///     DlsiteClient,
+    /// };
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = DlsiteClient::default();
+    ///     let ranking = client
+    ///         .ranking()
+    ///         .get(RankingTerm::Week, RankingCategory::Voice)
+    ///         .await
+    ///         .unwrap();
+    ///     for entry in ranking {
+    ///         println!("{}. {} ({})", entry.rank, entry.title, entry.id);
+    ///     }
+    /// }
+    /// ```
+    pub async fn get(
+        &self,
+        term: RankingTerm,
+        category: RankingCategory,
+    ) -> Result<Vec<RankingEntry>> {
+        let path = match category {
+            RankingCategory::All => format!("/ranking/{term}"),
+            category => format!("/ranking/{term}?category={category}"),
+        };
+        let html = self.c.get(&path).await?;
+        let parsed = parse_ranking_html(&html);
+
+        self.c.parse_context(parsed, &path, None, &html)
+    }
+}
+
+/// Parse a ranking page.
+pub(crate) fn parse_ranking_html(html: &str) -> Result<Vec<RankingEntry>> {
+    let html = Html::parse_document(html);
+    let mut entries = vec![];
+
+    for row in html.select(&Selector::parse("#ranking_table tr").unwrap()) {
+        let text = |selector: &str| {
+            row.select(&Selector::parse(selector).unwrap())
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+        };
+        // Header and ad rows don't have a rank.
+        let Some(rank) = text(".rank_no") else {
+            continue;
+        };
+        let rank = rank
+            .trim_end_matches('位')
+            .replace(',', "")
+            .parse()
+            .to_field_error(".rank_no", "Failed to parse rank")?;
+
+        let work = row
+            .select(&Selector::parse(".work_name a").unwrap())
+            .next()
+            .to_field_error(".work_name a", "No work found")?;
+        let id = DlsiteUrl::parse(work.value().attr("href").unwrap_or_default())
+            .ok()
+            .and_then(|url| url.product_id().cloned())
+            .to_field_error(".work_name a", "Failed to parse product id")?;
+        let title = work
+            .value()
+            .attr("title")
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| work.text().collect::<String>().trim().to_string());
+
+        let maker = row
+            .select(&Selector::parse(".maker_name a").unwrap())
+            .next();
+        let circle_name = maker
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
+        let circle_id = maker
+            .and_then(|e| DlsiteUrl::parse(e.value().attr("href")?).ok())
+            .and_then(|url| url.maker_id().cloned());
+
+        let price = text(".work_price .work_price_base")
+            .map(|p| p.replace(',', "").parse())
+            .transpose()
+            .to_field_error(".work_price .work_price_base", "Failed to parse price")?;
+
+        entries.push(RankingEntry {
+            rank,
+            id,
+            title,
+            circle_name,
+            circle_id,
+            price,
+            movement: parse_movement(row),
+        });
+    }
+
+    if entries.is_empty() {
+        return Err(ParseError::new("No ranking entry found")
+            .field("#ranking_table tr")
+            .into());
+    }
+
+    Ok(entries)
+}
+
+fn parse_movement(row: ElementRef) -> Option<RankMovement> {
+    let element = row.select(&Selector::parse(".rank_move").unwrap()).next()?;
+    element.value().classes().find_map(|class| match class {
+        "up" => Some(RankMovement::Up),
+        "down" => Some(RankMovement::Down),
+        "stay" => Some(RankMovement::Stay),
+        "new" => Some(RankMovement::New),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use test_case::test_case;
+
+    use super::{parse_ranking_html, RankMovement};
+    use crate::{
+        client::{
+            cassette::test_client,
+            ranking::{RankingCategory, RankingTerm},
+            stub::{StubResponse, StubServer},
+        },
+        DlsiteClient,
+    };
+
+    const RANKING_HTML: &str = r#"<html><body><table id="ranking_table">
+<tr><th>順位</th><th>作品</th></tr>
+<tr>
+  <td class="work_rank"><div class="rank_no">1位</div><div class="rank_move up"></div></td>
+  <td><dl>
+    <dt class="work_name"><a href="https://www.dlsite.com/maniax/work/=/product_id/RJ01000001.html" title="First">First</a></dt>
+    <dd class="maker_name"><a href="https://www.dlsite.com/maniax/circle/profile/=/maker_id/RG24350.html">Circle A</a></dd>
+  </dl></td>
+  <td><span class="work_price"><span class="work_price_base">1,100</span></span></td>
+</tr>
+<tr>
+  <td class="work_rank"><div class="rank_no">2</div><div class="rank_move new"></div></td>
+  <td><dl>
+    <dt class="work_name"><a href="/maniax/work/=/product_id/RJ01000002.html">Second</a></dt>
+    <dd class="maker_name"><a href="/maniax/circle/profile/=/maker_id/RG01000001.html">Circle B</a></dd>
+  </dl></td>
+</tr>
+</table></body></html>"#;
+
+    #[test]
+    fn parse_ranking() {
+        let entries = parse_ranking_html(RANKING_HTML).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].rank, 1);
+        assert_eq!(entries[0].id, "RJ01000001");
+        assert_eq!(entries[0].title, "First");
+        assert_eq!(entries[0].circle_name, "Circle A");
+        assert_eq!(entries[0].circle_id.as_ref().unwrap(), "RG24350");
+        assert_eq!(entries[0].price, Some(1100));
+        assert_eq!(entries[0].movement, Some(RankMovement::Up));
+
+        assert_eq!(entries[1].rank, 2);
+        assert_eq!(entries[1].title, "Second");
+        assert_eq!(entries[1].price, None);
+        assert_eq!(entries[1].movement, Some(RankMovement::New));
+
+        assert!(parse_ranking_html("<html></html>").is_err());
+    }
+
+    #[tokio::test]
+    async fn ranking_path() {
+        let server = StubServer::start(vec![StubResponse::ok(RANKING_HTML); 2]).await;
+        let client = DlsiteClient::builder()
+            .base_url(&format!("{}/maniax", server.url()))
+            .build()
+            .unwrap();
+        client
+            .ranking()
+            .get(RankingTerm::Day, RankingCategory::All)
+            .await
+            .unwrap();
+        client
+            .ranking()
+            .get(RankingTerm::Week, RankingCategory::Voice)
+            .await
+            .unwrap();
+        assert_eq!(
+            server.paths(),
+            vec![
+                "/maniax/ranking/day?locale=ja_JP",
+                "/maniax/ranking/week?category=voice&locale=ja_JP"
+            ]
+        );
+    }
+
+    #[test_case(RankingTerm::Day; "day")]
+    #[test_case(RankingTerm::Week; "week")]
+    #[test_case(RankingTerm::Month; "month")]
+    #[test_case(RankingTerm::Year; "year")]
+    #[test_case(RankingTerm::Total; "total")]
+    #[tokio::test]
+    async fn get_ranking(term: RankingTerm) {
+        let client = test_client(&format!("ranking/get_ranking/{term}"));
+        let entries = client
+            .ranking()
+            .get(term, RankingCategory::All)
+            .await
+            .unwrap();
+
+        assert_eq!(entries[0].rank, 1);
+        assert!(entries.windows(2).all(|w| w[0].rank <= w[1].rank));
+        let ids: HashSet<_> = entries.iter().map(|e| &e.id).collect();
+        assert_eq!(ids.len(), entries.len());
+        assert!(entries.iter().all(|e| !e.title.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn get_ranking_category() {
+        let client = test_client("ranking/get_ranking_category");
+        let entries = client
+            .ranking()
+            .get(RankingTerm::Week, RankingCategory::Voice)
+            .await
+            .unwrap();
+
+        assert!(!entries.is_empty());
+    }
+}
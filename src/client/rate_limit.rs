@@ -0,0 +1,250 @@
+//! Client-side rate limiting. For more information, see [`RateLimitConfig`].
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use super::Endpoint;
+
+/// Number of requests allowed in a period. Up to `requests` can be sent at once (burst).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rate {
+    pub requests: u32,
+    pub per: Duration,
+}
+
+impl Rate {
+    /// `requests` per second.
+    pub fn per_second(requests: u32) -> Self {
+        Self {
+            requests,
+            per: Duration::from_secs(1),
+        }
+    }
+
+    /// `requests` per minute.
+    pub fn per_minute(requests: u32) -> Self {
+        Self {
+            requests,
+            per: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Configuration of the token bucket rate limiter of [`DlsiteClient`](super::DlsiteClient).
+///
+/// Each host has its own budget. Rules for an [`Endpoint`] give that endpoint a separate budget
+/// on each host. When multiple rules match a request, the most specific one is used:
+/// host + endpoint, endpoint, host, then default.
+///
+/// The limiter is shared by all clones of a client and all of its sub-clients.
+///
+/// # Example
+/// ```
+/// use dlsite::{
+///     client::{rate_limit::{Rate, RateLimitConfig}, Endpoint},
+///     DlsiteClient,
+/// };
+///
+/// let client = DlsiteClient::builder()
+///     .rate_limit(
+///         RateLimitConfig::new(Rate::per_second(2))
+///             .endpoint(Endpoint::Ajax, Rate::per_second(5))
+///             .endpoint(Endpoint::Review, Rate::per_minute(30)),
+///     )
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitConfig {
+    default: Option<Rate>,
+    hosts: HashMap<String, Rate>,
+    endpoints: HashMap<Endpoint, Rate>,
+    host_endpoints: HashMap<(String, Endpoint), Rate>,
+}
+
+impl RateLimitConfig {
+    /// Limit all hosts with `rate` by default.
+    pub fn new(rate: Rate) -> Self {
+        Self {
+            default: Some(rate),
+            ..Default::default()
+        }
+    }
+
+    /// Limit requests to `host` with `rate`.
+    pub fn host(mut self, host: &str, rate: Rate) -> Self {
+        self.hosts.insert(host.to_string(), rate);
+        self
+    }
+
+    /// Give `endpoint` a separate budget of `rate` on each host.
+    pub fn endpoint(mut self, endpoint: Endpoint, rate: Rate) -> Self {
+        self.endpoints.insert(endpoint, rate);
+        self
+    }
+
+    /// Give `endpoint` on `host` a separate budget of `rate`.
+    pub fn host_endpoint(mut self, host: &str, endpoint: Endpoint, rate: Rate) -> Self {
+        self.host_endpoints
+            .insert((host.to_string(), endpoint), rate);
+        self
+    }
+
+    fn resolve(&self, host: &str, endpoint: Endpoint) -> Option<(BucketKey, Rate)> {
+        let host = host.to_string();
+        if let Some(rate) = self.host_endpoints.get(&(host.clone(), endpoint)) {
+            return Some(((host, Some(endpoint)), *rate));
+        }
+        if let Some(rate) = self.endpoints.get(&endpoint) {
+            return Some(((host, Some(endpoint)), *rate));
+        }
+        let rate = self.hosts.get(&host).or(self.default.as_ref())?;
+        Some(((host, None), *rate))
+    }
+}
+
+type BucketKey = (String, Option<Endpoint>);
+
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens. Negative value means there are requests waiting for tokens.
+    tokens: f64,
+    updated_at: Instant,
+}
+
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<BucketKey, Bucket>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait until a request to `host` and `endpoint` is allowed.
+    pub(crate) async fn acquire(&self, host: &str, endpoint: Endpoint) {
+        let Some((key, rate)) = self.config.resolve(host, endpoint) else {
+            return;
+        };
+        if rate.requests == 0 {
+            return;
+        }
+        let capacity = rate.requests as f64;
+        let per_token = rate.per.as_secs_f64() / capacity;
+
+        let wait = {
+            let mut buckets = self.buckets.lock().unwrap();
+            let now = Instant::now();
+            let bucket = buckets.entry(key).or_insert(Bucket {
+                tokens: capacity,
+                updated_at: now,
+            });
+            let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed / per_token).min(capacity);
+            bucket.updated_at = now;
+            // Reserve a token. If none is available, wait until it is refilled.
+            bucket.tokens -= 1.0;
+            if bucket.tokens >= 0.0 {
+                None
+            } else {
+                Some(Duration::from_secs_f64(-bucket.tokens * per_token))
+            }
+        };
+
+        if let Some(wait) = wait {
+            tracing::debug!("Rate limited: waiting {wait:?} for {host} ({endpoint:?})");
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{Rate, RateLimitConfig};
+    use crate::{
+        client::{
+            stub::{StubResponse, StubServer},
+            Endpoint,
+        },
+        DlsiteClient,
+    };
+
+    fn rate() -> Rate {
+        Rate {
+            requests: 1,
+            per: Duration::from_millis(200),
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limit_shared_by_clones() {
+        let server = StubServer::start(vec![StubResponse::ok(""); 3]).await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .rate_limit(RateLimitConfig::new(rate()))
+            .build()
+            .unwrap();
+
+        let start = Instant::now();
+        let cloned = client.clone();
+        tokio::try_join!(client.get("/a"), cloned.get("/b"), client.get("/c")).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(400));
+        assert_eq!(server.hits(), 3);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_separate_endpoint() {
+        let server = StubServer::start(vec![StubResponse::ok(""); 2]).await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .rate_limit(RateLimitConfig::new(rate()).endpoint(Endpoint::Ajax, rate()))
+            .build()
+            .unwrap();
+
+        let start = Instant::now();
+        tokio::try_join!(
+            client.get("/work/=/product_id/RJ01014447.html"),
+            client.get("/product/info/ajax?product_id=RJ01014447")
+        )
+        .unwrap();
+        assert!(start.elapsed() < Duration::from_millis(200));
+    }
+
+    #[test]
+    fn rate_limit_resolve() {
+        let config = RateLimitConfig::default()
+            .host("www.dlsite.com", Rate::per_second(1))
+            .endpoint(Endpoint::Review, Rate::per_second(2))
+            .host_endpoint("www.dlsite.com", Endpoint::Review, Rate::per_second(3));
+
+        assert_eq!(
+            config.resolve("www.dlsite.com", Endpoint::Html).unwrap().1,
+            Rate::per_second(1)
+        );
+        assert_eq!(
+            config
+                .resolve("www.dlsite.com", Endpoint::Review)
+                .unwrap()
+                .1,
+            Rate::per_second(3)
+        );
+        assert_eq!(
+            config
+                .resolve("play.dlsite.com", Endpoint::Review)
+                .unwrap()
+                .1,
+            Rate::per_second(2)
+        );
+        assert!(config.resolve("play.dlsite.com", Endpoint::Html).is_none());
+    }
+}
@@ -0,0 +1,360 @@
+//! Retry policy for transient failures. For more information, see [`RetryPolicy`].
+
+use std::{io::ErrorKind, time::Duration};
+
+use rand::Rng as _;
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    StatusCode,
+};
+
+use crate::DlsiteError;
+
+/// Policy to retry requests which failed because of transient errors.
+///
+/// A request is retried when it fails to connect, times out or the connection is closed before
+/// the response, or when the response status is in [`RetryPolicy::retry_statuses`]. Other errors,
+/// including all errors of a custom [`Transport`](super::transport::Transport), are not retried.
+/// The delay grows exponentially from [`RetryPolicy::base_delay`] up to
+/// [`RetryPolicy::max_delay`]. If the server sends `Retry-After`, that delay is used instead, also
+/// capped at [`RetryPolicy::max_delay`].
+///
+/// [`DlsiteClient`](super::DlsiteClient) does not retry by default. Use
+/// [`DlsiteClientBuilder::retry_policy`](super::builder::DlsiteClientBuilder::retry_policy) to
+/// enable it.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use dlsite::{client::retry::RetryPolicy, DlsiteClient};
+///
+/// let client = DlsiteClient::builder()
+///     .retry_policy(RetryPolicy {
+///         max_attempts: 5,
+///         base_delay: Duration::from_secs(1),
+///         ..Default::default()
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Upper bound of the exponential delay and `Retry-After`.
+    pub max_delay: Duration,
+    /// Randomize delays so that concurrent requests don't retry at once.
+    pub jitter: bool,
+    /// Response statuses to retry.
+    pub retry_statuses: Vec<StatusCode>,
+    /// Wait as long as the `Retry-After` header says.
+    pub respect_retry_after: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            retry_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy which never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Delay before the `attempt`-th retry (starting from 1).
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        if self.jitter && !exp.is_zero() {
+            // Equal jitter: half of the delay is fixed and the other half is random.
+            let half = exp / 2;
+            half + rand::rng().random_range(Duration::ZERO..=half)
+        } else {
+            exp
+        }
+    }
+
+    /// Delay before retrying a response with `status`, or `None` if it shouldn't be retried.
+    pub(crate) fn delay_for_status(
+        &self,
+        attempt: u32,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts || !self.retry_statuses.contains(&status) {
+            return None;
+        }
+        let retry_after = self
+            .respect_retry_after
+            .then(|| parse_retry_after(headers))
+            .flatten()
+            .map(|delay| delay.min(self.max_delay));
+        Some(retry_after.unwrap_or_else(|| self.backoff(attempt)))
+    }
+
+    /// Delay before retrying a failed request, or `None` if it shouldn't be retried.
+    pub(crate) fn delay_for_error(&self, attempt: u32, error: &DlsiteError) -> Option<Duration> {
+        if attempt >= self.max_attempts || !is_transient(error) {
+            return None;
+        }
+        Some(self.backoff(attempt))
+    }
+}
+
+fn is_transient(error: &DlsiteError) -> bool {
+    match error {
+        DlsiteError::Reqwest(e) => {
+            e.is_connect() || e.is_timeout() || (e.is_request() && is_connection_closed(e))
+        }
+        _ => false,
+    }
+}
+
+/// Whether the connection was reset or aborted while sending the request, as opposed to e.g. a
+/// malformed request.
+fn is_connection_closed(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(e) = source {
+        if let Some(e) = e.downcast_ref::<std::io::Error>() {
+            if matches!(
+                e.kind(),
+                ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::UnexpectedEof
+            ) {
+                return true;
+            }
+        }
+        source = e.source();
+    }
+    false
+}
+
+/// Parse `Retry-After` header, which is either delay seconds or HTTP-date.
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+        .or(Some(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::StatusCode;
+
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::RetryPolicy;
+    use crate::{
+        client::{
+            stub::{StubResponse, StubServer},
+            transport::{BoxFuture, Request, Response, Transport},
+        },
+        error::Result,
+        DlsiteClient, DlsiteError,
+    };
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(10),
+            jitter: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn backoff_exponential() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            jitter: false,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn backoff_jitter() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let delay = policy.backoff(2);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_on_status() {
+        let server = StubServer::start(vec![
+            StubResponse::status(503),
+            StubResponse::status(502),
+            StubResponse::ok("ok"),
+        ])
+        .await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .retry_policy(policy())
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get("/foo").await.unwrap(), "ok");
+        assert_eq!(
+            server.paths(),
+            vec![
+                "/foo?locale=ja_JP",
+                "/foo?locale=ja_JP",
+                "/foo?locale=ja_JP"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_on_reset() {
+        let server = StubServer::start(vec![StubResponse::Reset, StubResponse::ok("ok")]).await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .retry_policy(policy())
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get("/").await.unwrap(), "ok");
+        assert_eq!(server.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn retry_gives_up() {
+        let server = StubServer::start(vec![
+            StubResponse::status(500),
+            StubResponse::status(500),
+            StubResponse::status(500),
+            StubResponse::ok("ok"),
+        ])
+        .await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .retry_policy(policy())
+            .build()
+            .unwrap();
+
+        let res = client.fetch(&format!("{}/", server.url())).await;
+        assert_eq!(res.unwrap().status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(server.hits(), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_not_listed_status() {
+        let server =
+            StubServer::start(vec![StubResponse::status(404), StubResponse::ok("ok")]).await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .retry_policy(policy())
+            .build()
+            .unwrap();
+
+        let res = client.fetch(&format!("{}/", server.url())).await;
+        assert_eq!(res.unwrap().status, StatusCode::NOT_FOUND);
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_after() {
+        let server = StubServer::start(vec![
+            StubResponse::status(429).header("Retry-After", "1"),
+            StubResponse::ok("ok"),
+        ])
+        .await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .retry_policy(policy())
+            .build()
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        assert_eq!(client.get("/").await.unwrap(), "ok");
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn retry_after_capped() {
+        let server = StubServer::start(vec![
+            StubResponse::status(429).header("Retry-After", "3600"),
+            StubResponse::ok("ok"),
+        ])
+        .await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .retry_policy(RetryPolicy {
+                max_delay: Duration::from_millis(10),
+                ..policy()
+            })
+            .build()
+            .unwrap();
+
+        let res = tokio::time::timeout(Duration::from_secs(5), client.get("/")).await;
+        assert_eq!(res.unwrap().unwrap(), "ok");
+    }
+
+    #[derive(Debug, Default)]
+    struct FailingTransport(Arc<AtomicUsize>);
+
+    impl Transport for FailingTransport {
+        fn send(&self, _request: Request) -> BoxFuture<'_, Result<Response>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Err(DlsiteError::Transport("No cassette".into())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn no_retry_on_transport_error() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = DlsiteClient::builder()
+            .transport(FailingTransport(calls.clone()))
+            .retry_policy(policy())
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            client.get("/").await,
+            Err(DlsiteError::Transport(_))
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
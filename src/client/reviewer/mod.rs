@@ -0,0 +1,378 @@
+//! Interfaces related to reviewers only. For more information, see [`ReviewerClient`].
+
+use std::future::ready;
+
+use chrono::{DateTime, FixedOffset};
+use futures::StreamExt as _;
+
+use super::{
+    paginate::{PageStream, StreamOptions},
+    product::review::{
+        fetch_review, review_stream, ProductReview, Review, ReviewQuery, ReviewStreamOptions,
+        ReviewerRank,
+    },
+    DlsiteClient,
+};
+use crate::{error::Result, DlsiteError};
+
+/// Client to get reviews written by a reviewer, identified by [`Review::reviewer_id`].
+///
+/// Reviews are per site, so use [`DlsiteClient::site`] to get reviews on other sites than the
+/// default one.
+#[derive(Clone, Debug)]
+pub struct ReviewerClient {
+    pub(crate) c: DlsiteClient,
+}
+
+/// Stats of a reviewer aggregated from the reviews by [`ReviewerClient::aggregate_reviews`].
+///
+/// These are computed by this crate, not read from a page of DLsite, and cover only the reviews
+/// which were aggregated.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ReviewerStats {
+    pub reviewer_id: String,
+    /// Nickname in the latest review.
+    pub nick_name: Option<String>,
+    /// Rank in the latest review.
+    pub rank: Option<ReviewerRank>,
+    /// Number of aggregated reviews.
+    pub review_count: usize,
+    /// Number of reviews of products which the reviewer purchased.
+    pub purchased_count: usize,
+    /// Number of reviews with spoilers.
+    pub spoiler_count: usize,
+    /// Total number of users who found the reviews helpful.
+    pub good_review_total: i64,
+    /// Total number of users who didn't find the reviews helpful.
+    pub bad_review_total: i64,
+    /// Average rating of reviews with a rating.
+    pub average_rate: Option<f32>,
+    pub first_review_at: Option<DateTime<FixedOffset>>,
+    pub latest_review_at: Option<DateTime<FixedOffset>>,
+}
+
+impl ReviewerClient {
+    /// Get a page of reviews written by a reviewer. `mix_pickup` of `query` has no effect.
+    ///
+    /// Returns [`DlsiteError::InvalidId`] if `reviewer_id` is empty or has characters other than
+    /// ASCII alphanumerics, `-` and `_`.
+    pub async fn get_reviews(
+        &self,
+        reviewer_id: &str,
+        query: &ReviewQuery,
+    ) -> Result<ProductReview> {
+        validate_reviewer_id(reviewer_id)?;
+        let path = query.to_reviewer_path(reviewer_id);
+        fetch_review(&self.c, &path, reviewer_id).await
+    }
+
+    /// Get all reviews written by a reviewer, fetching pages until an empty page since the review
+    /// count of a reviewer is not available. For how pages are fetched, see [`StreamOptions`]. An
+    /// invalid `reviewer_id` is yielded as an error, like [`get_reviews`](Self::get_reviews).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlsite::{
+    ///     client::{paginate::StreamOptions, product::review::ReviewStreamOptions},
+    ///     DlsiteClient,
+    /// };
+    /// use futures::StreamExt as _;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = DlsiteClient::default();
+    ///     let mut stream = client.reviewer().review_stream(
+    ///         "123456",
+    ///         &ReviewStreamOptions::default(),
+    ///         StreamOptions::default(),
+    ///     );
+    ///     while let Some(review) = stream.next().await {
+    ///         let review = review.unwrap();
+    ///         println!("{}: {}", review.workno, review.review_title);
+    ///     }
+    /// }
+    /// ```
+    pub fn review_stream(
+        &self,
+        reviewer_id: &str,
+        options: &ReviewStreamOptions,
+        stream_options: StreamOptions,
+    ) -> PageStream<Review> {
+        if let Err(e) = validate_reviewer_id(reviewer_id) {
+            return PageStream::failed(e);
+        }
+        review_stream(
+            self.c.clone(),
+            "reviewer_id",
+            reviewer_id.to_string(),
+            options,
+            stream_options,
+            || ready(Ok(None)),
+        )
+    }
+
+    /// Aggregate reviews written by a reviewer into [`ReviewerStats`], e.g. to spot reviewers who
+    /// post many reviews of products they didn't purchase.
+    ///
+    /// Reviews are fetched page by page from the newest, up to `max_items` of `stream_options`.
+    /// Without `max_items`, all reviews of the reviewer are fetched, which takes as many requests
+    /// as pages.
+    pub async fn aggregate_reviews(
+        &self,
+        reviewer_id: &str,
+        stream_options: StreamOptions,
+    ) -> Result<ReviewerStats> {
+        let mut stats = ReviewerStats {
+            reviewer_id: reviewer_id.to_string(),
+            nick_name: None,
+            rank: None,
+            review_count: 0,
+            purchased_count: 0,
+            spoiler_count: 0,
+            good_review_total: 0,
+            bad_review_total: 0,
+            average_rate: None,
+            first_review_at: None,
+            latest_review_at: None,
+        };
+        let (mut rate_sum, mut rate_count) = (0, 0);
+
+        let mut stream =
+            self.review_stream(reviewer_id, &ReviewStreamOptions::default(), stream_options);
+        while let Some(review) = stream.next().await {
+            let review = review?;
+            if stats
+                .latest_review_at
+                .is_none_or(|at| at < review.regist_date)
+            {
+                stats.latest_review_at = Some(review.regist_date);
+                stats.nick_name = review.nick_name.clone();
+                stats.rank = Some(review.reviewer_rank.clone());
+            }
+            if stats
+                .first_review_at
+                .is_none_or(|at| at > review.regist_date)
+            {
+                stats.first_review_at = Some(review.regist_date);
+            }
+            stats.review_count += 1;
+            stats.purchased_count += usize::from(review.is_purchased);
+            stats.spoiler_count += usize::from(review.spoiler);
+            stats.good_review_total += i64::from(review.good_review);
+            stats.bad_review_total += i64::from(review.bad_review);
+            if let Some(rate) = review.rate {
+                rate_sum += rate;
+                rate_count += 1;
+            }
+        }
+        if rate_count > 0 {
+            stats.average_rate = Some(rate_sum as f32 / rate_count as f32);
+        }
+
+        Ok(stats)
+    }
+}
+
+/// Reviewer IDs are numbers as far as seen, but allow alphanumerics in case they change.
+fn validate_reviewer_id(reviewer_id: &str) -> Result<()> {
+    if reviewer_id.is_empty()
+        || !reviewer_id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    {
+        return Err(DlsiteError::InvalidId(format!(
+            "{reviewer_id:?} is not a valid reviewer ID"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt as _;
+
+    use crate::{
+        client::{
+            cassette::test_client,
+            paginate::StreamOptions,
+            product::review::{
+                test_json::{review_page, TestReview},
+                ReviewQuery, ReviewStreamOptions, ReviewerRank,
+            },
+            stub::{StubResponse, StubServer},
+        },
+        DlsiteClient, DlsiteError,
+    };
+
+    /// A review by reviewer 42, who ranks third.
+    fn review<'a>(
+        id: &'a str,
+        workno: &'a str,
+        date: &'a str,
+        rate: &'a str,
+        good: &'a str,
+    ) -> TestReview<'a> {
+        TestReview {
+            workno,
+            reviewer_id: "42",
+            good_review: good,
+            bad_review: "1",
+            nick_name: Some("nick"),
+            rate,
+            rate_num: "0",
+            reviewer_rank: "3",
+            ..TestReview::new(id, date)
+        }
+    }
+
+    #[tokio::test]
+    async fn reviewer_reviews() {
+        let server = StubServer::start(vec![StubResponse::ok(&review_page(&[review(
+            "1",
+            "RJ01000001",
+            "2023-03-01 00:00:00",
+            "5",
+            "2",
+        )]))])
+        .await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .build()
+            .unwrap();
+        let reviews = client
+            .reviewer()
+            .get_reviews("42", &ReviewQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(reviews.review_list[0].workno, "RJ01000001");
+        assert!(server.paths()[0].starts_with("/api/review?reviewer_id=42&limit=6"));
+    }
+
+    #[tokio::test]
+    async fn invalid_reviewer_id() {
+        let server = StubServer::start(vec![]).await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .build()
+            .unwrap();
+        for id in ["", "42&product_id=RJ01014447", "4 2"] {
+            assert!(matches!(
+                client
+                    .reviewer()
+                    .get_reviews(id, &ReviewQuery::default())
+                    .await,
+                Err(DlsiteError::InvalidId(_))
+            ));
+            let items: Vec<_> = client
+                .reviewer()
+                .review_stream(
+                    id,
+                    &ReviewStreamOptions::default(),
+                    StreamOptions::default(),
+                )
+                .collect()
+                .await;
+            assert!(matches!(items[..], [Err(DlsiteError::InvalidId(_))]));
+        }
+        assert_eq!(server.hits(), 0);
+    }
+
+    #[tokio::test]
+    async fn get_reviews_of_reviewer() {
+        // Take a reviewer from a product so that the test doesn't depend on a fixed reviewer.
+        let client = test_client("reviewer/get_reviews_of_reviewer");
+        let product_id = "RJ01014447".parse().unwrap();
+        let product_reviews = client
+            .product()
+            .get_review(&product_id, &ReviewQuery::default())
+            .await
+            .unwrap();
+        let reviewer_id = &product_reviews.review_list[0].reviewer_id;
+
+        let reviews = client
+            .reviewer()
+            .get_reviews(
+                reviewer_id,
+                &ReviewQuery {
+                    limit: Some(50),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(reviews
+            .review_list
+            .iter()
+            .all(|r| &r.reviewer_id == reviewer_id));
+        assert!(reviews.review_list.iter().any(|r| r.workno == product_id));
+    }
+
+    #[tokio::test]
+    async fn aggregate_reviews() {
+        let server = StubServer::start(vec![
+            StubResponse::ok(&review_page(&[
+                review("3", "RJ01000003", "2023-03-03 00:00:00", "5", "10"),
+                review("2", "RJ01000002", "2023-03-02 00:00:00", "", "0"),
+            ])),
+            StubResponse::ok(&review_page(&[review(
+                "1",
+                "RJ01000001",
+                "2023-03-01 00:00:00",
+                "2",
+                "1",
+            )])),
+            StubResponse::ok(&review_page(&[])),
+            StubResponse::ok(&review_page(&[review(
+                "3",
+                "RJ01000003",
+                "2023-03-03 00:00:00",
+                "5",
+                "10",
+            )])),
+        ])
+        .await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .build()
+            .unwrap();
+        let stats = client
+            .reviewer()
+            .aggregate_reviews("42", StreamOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(stats.review_count, 3);
+        assert_eq!(stats.purchased_count, 3);
+        assert_eq!(stats.good_review_total, 11);
+        assert_eq!(stats.bad_review_total, 3);
+        assert_eq!(stats.average_rate, Some(3.5));
+        assert_eq!(stats.nick_name.as_deref(), Some("nick"));
+        assert_eq!(stats.rank, Some(ReviewerRank::Ranked(3)));
+        assert_eq!(
+            stats.first_review_at.unwrap().to_string(),
+            "2023-03-01 00:00:00 +09:00"
+        );
+        assert_eq!(
+            stats.latest_review_at.unwrap().to_string(),
+            "2023-03-03 00:00:00 +09:00"
+        );
+
+        let stats = client
+            .reviewer()
+            .aggregate_reviews(
+                "42",
+                StreamOptions {
+                    max_items: Some(1),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(stats.review_count, 1);
+        assert_eq!(server.hits(), 4);
+        assert_eq!(
+            serde_json::to_value(&stats).unwrap()["rank"],
+            serde_json::json!("3")
+        );
+    }
+}
@@ -40,13 +40,3 @@ macro_rules! push_option_bool {
     };
 }
 pub(crate) use push_option_bool;
-
-macro_rules! push {
-    ($path:expr, $self:ident, $property:ident) => {
-        $path.push_str("/");
-        $path.push_str(stringify!($property));
-        $path.push_str("/");
-        $path.push_str(&$self.$property.to_string());
-    };
-}
-pub(crate) use push;
@@ -6,9 +6,14 @@ mod query;
 use scraper::{Html, Selector};
 use serde::Deserialize;
 
+use super::paginate::{paginate, Page, PageStream, StreamOptions};
 use crate::{
-    error::Result,
-    interface::product::{AgeCategory, WorkType},
+    error::{ParseError, Result},
+    interface::{
+        id::{MakerId, ProductId},
+        product::{AgeCategory, WorkType},
+        url::DlsiteUrl,
+    },
     utils::ToParseError,
     DlsiteClient,
 };
@@ -16,8 +21,9 @@ use crate::{
 pub use self::query::SearchProductQuery;
 
 /// Client to search products on DLsite.
-pub struct SearchClient<'a> {
-    pub(crate) c: &'a DlsiteClient,
+#[derive(Clone, Debug)]
+pub struct SearchClient {
+    pub(crate) c: DlsiteClient,
 }
 
 #[derive(Deserialize)]
@@ -33,12 +39,12 @@ struct SearchAjaxResult {
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct SearchProductItem {
-    pub id: String,
+    pub id: ProductId,
     pub title: String,
     pub creator: Option<String>,
     pub creator_omitted: Option<bool>,
     pub circle_name: String,
-    pub circle_id: String,
+    pub circle_id: MakerId,
     pub dl_count: Option<i32>,
     pub rate_count: Option<i32>,
     pub review_count: Option<i32>,
@@ -68,7 +74,7 @@ fn parse_num_str(str: &str) -> Result<i32> {
         .to_parse_error("Failed to parse string to number")
 }
 
-impl<'a> SearchClient<'a> {
+impl SearchClient {
     /// Search products on DLsite.
     ///
     /// # Arguments
@@ -94,13 +100,18 @@ impl<'a> SearchClient<'a> {
     /// }
     /// ```
     pub async fn search_product(&self, options: &SearchProductQuery) -> Result<SearchResult> {
-        let query_path = options.to_path();
+        let query_path = match options.language {
+            Some(_) => options.to_path(),
+            None => SearchProductQuery {
+                language: Some(self.c.locale().into()),
+                ..options.clone()
+            }
+            .to_path(),
+        };
         let json = self.c.get(&query_path).await?;
-        let json = serde_json::from_str::<SearchAjaxResult>(&json)?;
-        let html = json.search_result;
-        let count = json.page_info.count;
 
-        let products = parse_search_html(&html)?;
+        let parsed = parse_search_ajax(&json);
+        let (products, count) = self.c.parse_context(parsed, &query_path, None, &json)?;
 
         Ok(SearchResult {
             products,
@@ -108,6 +119,76 @@ impl<'a> SearchClient<'a> {
             query_path,
         })
     }
+
+    /// Search products on DLsite, fetching all pages automatically. For how pages are fetched,
+    /// see [`StreamOptions`].
+    ///
+    /// `page` of `options` is ignored and the stream starts from the first page. Set `per_page`
+    /// to 100 to reduce requests.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlsite::{
+    ///     client::{paginate::StreamOptions, search::SearchProductQuery},
+    ///     DlsiteClient,
+    /// };
+    /// use futures::StreamExt as _;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = DlsiteClient::default();
+    ///     let query = SearchProductQuery {
+    ///         keyword: Some("ASMR".to_string()),
+    ///         per_page: Some(100),
+    ///         ..Default::default()
+    ///     };
+    ///     let options = StreamOptions {
+    ///         max_items: Some(500),
+    ///         ..Default::default()
+    ///     };
+    ///     let mut stream = client.search().search_product_stream(&query, options);
+    ///     while let Some(item) = stream.next().await {
+    ///         println!("{}", item.unwrap().title);
+    ///     }
+    /// }
+    /// ```
+    pub fn search_product_stream(
+        &self,
+        options: &SearchProductQuery,
+        stream_options: StreamOptions,
+    ) -> PageStream<SearchProductItem> {
+        let client = self.clone();
+        let query = options.clone();
+        let fetch = move |page| {
+            let client = client.clone();
+            let query = SearchProductQuery {
+                page: Some(page),
+                ..query.clone()
+            };
+            async move {
+                let result = client.search_product(&query).await?;
+                Ok(Page {
+                    items: result.products,
+                    total: Some(result.count.max(0) as usize),
+                })
+            }
+        };
+
+        paginate(
+            stream_options,
+            options.per_page.map(|n| n as usize),
+            fetch,
+            |item| item.id.clone(),
+        )
+    }
+}
+
+/// Parse json returned by the search ajax api into products and total count.
+pub(crate) fn parse_search_ajax(json: &str) -> Result<(Vec<SearchProductItem>, i32)> {
+    let json = serde_json::from_str::<SearchAjaxResult>(json)?;
+    let products = parse_search_html(&json.search_result)?;
+
+    Ok((products, json.page_info.count))
 }
 
 pub(crate) fn parse_search_html(html: &str) -> Result<Vec<SearchProductItem>> {
@@ -118,12 +199,12 @@ pub(crate) fn parse_search_html(html: &str) -> Result<Vec<SearchProductItem>> {
         let product_id_e = item_element
             .select(&Selector::parse("div[data-product_id]").unwrap())
             .next()
-            .to_parse_error("Failed to find data element")?
+            .to_field_error("div[data-product_id]", "Failed to find data element")?
             .value();
         let maker_e = item_element
             .select(&Selector::parse(".maker_name a").unwrap())
             .next()
-            .to_parse_error("Failed to find maker element")?;
+            .to_field_error(".maker_name a", "Failed to find maker element")?;
         let author_e = item_element
             .select(&Selector::parse(".author").unwrap())
             .next();
@@ -131,7 +212,10 @@ pub(crate) fn parse_search_html(html: &str) -> Result<Vec<SearchProductItem>> {
         let price_e = item_element
             .select(&Selector::parse(".work_price .work_price_base").unwrap())
             .next()
-            .to_parse_error("Failed to find price element")?;
+            .to_field_error(
+                ".work_price .work_price_base",
+                "Failed to find price element",
+            )?;
         let original_price_e = item_element
             .select(&Selector::parse(".work_price_wrap .strike .work_price_base").unwrap())
             .next();
@@ -143,14 +227,15 @@ pub(crate) fn parse_search_html(html: &str) -> Result<Vec<SearchProductItem>> {
         let id = product_id_e
             .attr("data-product_id")
             .to_parse_error("Failed to get product id")?
-            .to_string();
+            .parse::<ProductId>()
+            .to_parse_error("Failed to parse product id")?;
 
         result.push(SearchProductItem {
             id: id.clone(),
             title: item_element
                 .select(&Selector::parse(".work_name a[title]").unwrap())
                 .next()
-                .to_parse_error("Failed to get title")?
+                .to_field_error(".work_name a[title]", "Failed to get title")?
                 .value()
                 .attr("title")
                 .unwrap()
@@ -166,32 +251,33 @@ pub(crate) fn parse_search_html(html: &str) -> Result<Vec<SearchProductItem>> {
                             "全年齢" => AgeCategory::General,
                             "R-15" => AgeCategory::R15,
                             _ => {
-                                return Err(crate::DlsiteError::Parse(
-                                    "Age category parse error: invalid title".to_string(),
-                                ))
+                                return Err(ParseError::new(
+                                    "Age category parse error: invalid title",
+                                )
+                                .field(".work_genre span")
+                                .into())
                             }
                         }
                     } else {
-                        return Err(crate::DlsiteError::Parse(
-                            "Age category parse error".to_string(),
-                        ));
+                        return Err(ParseError::new("Age category parse error")
+                            .field(".work_genre span")
+                            .into());
                     }
                 } else {
                     AgeCategory::Adult
                 }
             },
             circle_name: maker_e.text().next().unwrap_or("").to_string(),
-            circle_id: maker_e
-                .value()
-                .attr("href")
-                .to_parse_error("Failed to get maker link")?
-                .split('/')
-                .next_back()
-                .to_parse_error("Invalid url")?
-                .split('.')
-                .next()
-                .to_parse_error("Failed to find maker id")?
-                .to_string(),
+            circle_id: DlsiteUrl::parse(
+                maker_e
+                    .value()
+                    .attr("href")
+                    .to_field_error(".maker_name a", "Failed to get maker link")?,
+            )
+            .to_field_error(".maker_name a", "Invalid url")?
+            .maker_id()
+            .cloned()
+            .to_field_error(".maker_name a", "Failed to find maker id")?,
             creator: {
                 if let Some(creator_e) = author_e {
                     let name = creator_e
@@ -280,7 +366,7 @@ pub(crate) fn parse_search_html(html: &str) -> Result<Vec<SearchProductItem>> {
             work_type: item_element
                 .select(&Selector::parse(".work_category").unwrap())
                 .next()
-                .to_parse_error("Failed to find work category")?
+                .to_field_error(".work_category", "Failed to find work category")?
                 .value()
                 .attr("class")
                 .to_parse_error("Failed to find worktype")?
@@ -302,7 +388,7 @@ pub(crate) fn parse_search_html(html: &str) -> Result<Vec<SearchProductItem>> {
                 let img_e = item_element
                     .select(&Selector::parse(".work_thumb_inner > img").unwrap())
                     .next()
-                    .to_parse_error("Failed to find thumbnail")?;
+                    .to_field_error(".work_thumb_inner > img", "Failed to find thumbnail")?;
 
                 let src = img_e.value().attr("src");
                 let data_src = img_e.value().attr("data-src");
@@ -310,9 +396,9 @@ pub(crate) fn parse_search_html(html: &str) -> Result<Vec<SearchProductItem>> {
                     (Some(src), _) => format!("https:{}", src),
                     (_, Some(data_src)) => format!("https:{}", data_src),
                     (_, _) => {
-                        return Err(crate::DlsiteError::Parse(
-                            "Failed to find thumbnail".to_string(),
-                        ))
+                        return Err(ParseError::new("Failed to find thumbnail")
+                            .field(".work_thumb_inner > img")
+                            .into())
                     }
                 }
             },
@@ -360,16 +446,55 @@ pub(crate) fn parse_search_html(html: &str) -> Result<Vec<SearchProductItem>> {
 #[cfg(test)]
 mod tests {
     use crate::{
-        client::DlsiteClient,
+        client::{
+            cassette::{test_client, test_client_builder},
+            stub::{StubResponse, StubServer},
+        },
         interface::{
+            locale::Locale,
             product::WorkType,
-            query::{Order, SexCategory},
+            query::{Language, Order, SexCategory},
         },
+        DlsiteClient,
     };
+    use test_case::test_case;
+
+    #[tokio::test]
+    async fn search_language_from_locale() {
+        let json = r#"{"search_result": "", "page_info": {"count": 0}}"#;
+        let server = StubServer::start(vec![StubResponse::ok(json); 2]).await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .locale(Locale::ChineseSimplified)
+            .build()
+            .unwrap();
+
+        let res = client
+            .search()
+            .search_product(&super::SearchProductQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(res.query_path, "/fsr/ajax/=/language/zh_cn");
+        client
+            .search()
+            .search_product(&super::SearchProductQuery {
+                language: Some(Language::Jp),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            server.paths(),
+            vec![
+                "/fsr/ajax/=/language/zh_cn?locale=zh_CN",
+                "/fsr/ajax/=/language/jp?locale=zh_CN"
+            ]
+        );
+    }
 
     #[tokio::test]
     async fn search_product_1() {
-        let client = DlsiteClient::default();
+        let client = test_client("search/search_product_1");
         let res = client
             .search()
             .search_product(&super::SearchProductQuery {
@@ -396,7 +521,7 @@ mod tests {
                 assert!(r.rate_count.is_some());
                 assert!(r.review_count.is_some());
                 assert!(r.rating.is_some());
-                assert_eq!("RG51654", r.circle_id);
+                assert_eq!(r.circle_id, "RG51654");
                 assert_eq!("CANDY VOICE", r.circle_name);
                 assert_eq!(WorkType::SOU, r.work_type);
                 assert_eq!("竹達彩奈", r.creator.as_ref().unwrap());
@@ -405,9 +530,36 @@ mod tests {
         });
     }
 
+    #[test_case(Locale::English; "en")]
+    #[test_case(Locale::ChineseSimplified; "zh_cn")]
+    #[test_case(Locale::ChineseTraditional; "zh_tw")]
+    #[test_case(Locale::Korean; "ko")]
+    #[tokio::test]
+    async fn search_product_localized(locale: Locale) {
+        let client = test_client_builder(&format!("search/search_product_localized/{locale}"))
+            .locale(locale)
+            .build()
+            .unwrap();
+        let res = client
+            .search()
+            .search_product(&super::SearchProductQuery {
+                sex_category: Some(vec![SexCategory::Male]),
+                order: Some(Order::Trend),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to search");
+
+        assert!(res
+            .query_path
+            .starts_with(&format!("/fsr/ajax/=/language/{}", Language::from(locale))));
+        assert!(!res.products.is_empty());
+        assert!(res.products.iter().all(|p| !p.title.is_empty()));
+    }
+
     #[tokio::test]
     async fn search_product_2() {
-        let client = DlsiteClient::default();
+        let client = test_client("search/search_product_2");
         let mut opts = super::SearchProductQuery {
             sex_category: Some(vec![SexCategory::Male]),
             order: Some(Order::Trend),
@@ -38,10 +38,10 @@ use crate::interface::query::*;
 // /from/fs.detail
 
 /// Struct to represent the search options for dlsite product search
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct SearchProductQuery {
-    /// Display lang
-    pub language: Language,
+    /// Display language. `None` uses the locale of the client, or `jp` in [`Self::to_path`].
+    pub language: Option<Language>,
     pub keyword_creator: Option<String>,
     pub sex_category: Option<Vec<SexCategory>>,
     pub keyword: Option<String>,
@@ -77,7 +77,8 @@ impl SearchProductQuery {
     pub fn to_path(&self) -> String {
         let mut path = "/fsr/ajax/=".to_string();
 
-        push!(path, self, language);
+        path.push_str("/language/");
+        path.push_str(&self.language.clone().unwrap_or_default().to_string());
         push_option!(path, self, keyword_creator);
         push_option_array!(path, self, sex_category);
         push_option!(path, self, keyword);
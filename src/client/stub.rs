@@ -0,0 +1,164 @@
+//! Local HTTP server returning scripted responses, used to test the client offline.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{
+    io::{AsyncReadExt as _, AsyncWriteExt as _},
+    net::TcpListener,
+};
+
+/// Scripted response of [`StubServer`].
+#[derive(Debug, Clone)]
+pub(crate) enum StubResponse {
+    /// Respond with status, headers and body.
+    Http {
+        status: u16,
+        headers: Vec<(String, String)>,
+        body: String,
+        /// Respond only to requests whose path starts with this.
+        path: Option<String>,
+    },
+    /// Reset the connection without responding.
+    Reset,
+}
+
+impl StubResponse {
+    pub(crate) fn ok(body: &str) -> Self {
+        Self::Http {
+            status: 200,
+            headers: vec![],
+            body: body.to_string(),
+            path: None,
+        }
+    }
+
+    pub(crate) fn status(status: u16) -> Self {
+        Self::Http {
+            status,
+            headers: vec![],
+            body: String::new(),
+            path: None,
+        }
+    }
+
+    pub(crate) fn header(mut self, name: &str, value: &str) -> Self {
+        if let Self::Http { headers, .. } = &mut self {
+            headers.push((name.to_string(), value.to_string()));
+        }
+        self
+    }
+
+    /// Respond only to requests whose path starts with `prefix`, for concurrent requests.
+    pub(crate) fn path(mut self, prefix: &str) -> Self {
+        if let Self::Http { path, .. } = &mut self {
+            *path = Some(prefix.to_string());
+        }
+        self
+    }
+
+    fn matches(&self, request_path: &str) -> bool {
+        match self {
+            Self::Http {
+                path: Some(prefix), ..
+            } => request_path.starts_with(prefix.as_str()),
+            _ => true,
+        }
+    }
+}
+
+/// HTTP server which returns [`StubResponse`]s in order, one per request. A response for a path
+/// is returned to the first request to the path.
+///
+/// Once all responses are consumed, it responds with 404.
+pub(crate) struct StubServer {
+    addr: std::net::SocketAddr,
+    paths: Arc<Mutex<Vec<String>>>,
+}
+
+impl StubServer {
+    pub(crate) async fn start(responses: Vec<StubResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let paths = Arc::new(Mutex::new(vec![]));
+        let responses = Arc::new(Mutex::new(VecDeque::from(responses)));
+
+        let paths_ = paths.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                let paths = paths_.clone();
+                let responses = responses.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![];
+                    let mut chunk = [0u8; 1024];
+                    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match socket.read(&mut chunk).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                        }
+                    }
+                    let request = String::from_utf8_lossy(&buf);
+                    let path = request
+                        .split_whitespace()
+                        .nth(1)
+                        .unwrap_or_default()
+                        .to_string();
+                    paths.lock().unwrap().push(path.clone());
+
+                    let response = {
+                        let mut responses = responses.lock().unwrap();
+                        responses
+                            .iter()
+                            .position(|r| r.matches(&path))
+                            .and_then(|i| responses.remove(i))
+                            .unwrap_or(StubResponse::status(404))
+                    };
+                    let StubResponse::Http {
+                        status,
+                        headers,
+                        body,
+                        ..
+                    } = response
+                    else {
+                        // Zero linger makes dropping the socket send RST instead of FIN.
+                        let _ = socket.set_linger(Some(std::time::Duration::ZERO));
+                        return;
+                    };
+                    let mut raw = format!(
+                        "HTTP/1.1 {status} STUB\r\nContent-Length: {}\r\nConnection: close\r\n",
+                        body.len()
+                    );
+                    for (name, value) in headers {
+                        raw.push_str(&format!("{name}: {value}\r\n"));
+                    }
+                    raw.push_str("\r\n");
+                    raw.push_str(&body);
+                    let _ = socket.write_all(raw.as_bytes()).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        Self { addr, paths }
+    }
+
+    /// Base URL of the server.
+    pub(crate) fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Number of requests received.
+    pub(crate) fn hits(&self) -> usize {
+        self.paths.lock().unwrap().len()
+    }
+
+    /// Paths of requests received, in order.
+    pub(crate) fn paths(&self) -> Vec<String> {
+        self.paths.lock().unwrap().clone()
+    }
+}
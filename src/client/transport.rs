@@ -0,0 +1,165 @@
+//! Pluggable HTTP transport used by [`DlsiteClient`](super::DlsiteClient).
+//!
+//! Every request made by the client and its sub-clients goes through a [`Transport`]. By default
+//! [`ReqwestTransport`] is used, but you can implement the trait yourself to plug in a middleware
+//! stack, an in-process fake for tests, or a different HTTP library.
+
+use std::{fmt::Debug, future::Future, pin::Pin};
+
+pub use reqwest::{header::HeaderMap, Method, StatusCode};
+
+use crate::error::Result;
+
+/// Boxed future returned by [`Transport::send`].
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// HTTP request passed to a [`Transport`].
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+}
+
+impl Request {
+    /// Create a GET request without extra headers.
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            method: Method::GET,
+            url: url.into(),
+            headers: HeaderMap::new(),
+        }
+    }
+}
+
+/// HTTP response returned by a [`Transport`].
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+/// Sends HTTP requests on behalf of [`DlsiteClient`](super::DlsiteClient).
+///
+/// # Example
+/// ```
+/// use dlsite::{
+///     client::transport::{BoxFuture, HeaderMap, Request, Response, StatusCode, Transport},
+///     DlsiteClient, DlsiteError,
+/// };
+///
+/// #[derive(Debug)]
+/// struct Fake;
+///
+/// impl Transport for Fake {
+///     fn send(&self, _request: Request) -> BoxFuture<'_, Result<Response, DlsiteError>> {
+///         Box::pin(async {
+///             Ok(Response {
+///                 status: StatusCode::OK,
+///                 headers: HeaderMap::new(),
+///                 body: "hello".to_string(),
+///             })
+///         })
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let client = DlsiteClient::with_transport("https://www.dlsite.com/maniax", Fake);
+///     assert_eq!(client.get("/").await.unwrap(), "hello");
+/// }
+/// ```
+pub trait Transport: Debug + Send + Sync {
+    /// Send a request and return the response. Non-2xx responses must be returned as `Ok`.
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response>>;
+}
+
+/// Default [`Transport`] implementation backed by [`reqwest::Client`].
+///
+/// TLS backend is selected by `reqwest-*-tls` features.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Create a transport using a pre-built reqwest client.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl From<reqwest::Client> for ReqwestTransport {
+    fn from(client: reqwest::Client) -> Self {
+        Self::new(client)
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+        Box::pin(async move {
+            let res = self
+                .client
+                .request(request.method, &request.url)
+                .headers(request.headers)
+                .send()
+                .await?;
+            let status = res.status();
+            let headers = res.headers().clone();
+            let body = res.text().await?;
+
+            Ok(Response {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::DlsiteClient;
+
+    #[derive(Debug, Default)]
+    struct RecordingTransport {
+        urls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Transport for RecordingTransport {
+        fn send(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+            self.urls.lock().unwrap().push(request.url);
+            Box::pin(async {
+                Ok(Response {
+                    status: StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: "body".to_string(),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_transport() {
+        let transport = RecordingTransport::default();
+        let urls = transport.urls.clone();
+        let client = DlsiteClient::with_transport("https://example.com/maniax", transport);
+
+        assert_eq!(client.get("/foo").await.unwrap(), "body");
+        assert_eq!(
+            client.get_raw("https://example.com/bar").await.unwrap(),
+            "body"
+        );
+        assert_eq!(
+            *urls.lock().unwrap(),
+            vec![
+                "https://example.com/maniax/foo?locale=ja_JP".to_string(),
+                "https://example.com/bar?locale=ja_JP".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Handling of unknown fields in JSON responses. For more information, see [`UnknownFieldPolicy`].
+
+use std::collections::HashSet;
+
+use serde::{de::DeserializeOwned, Deserializer};
+
+use super::Endpoint;
+use crate::{
+    error::{ParseError, Result},
+    interface::id::ProductId,
+};
+
+/// What to do when a JSON response has fields which this crate doesn't know.
+///
+/// This applies to the product API (`product.json`), the ajax product info API and the review API.
+/// Unknown fields usually mean DLsite added something, and the response is still parsed.
+///
+/// Note that `unknown-field-error` feature makes unknown fields an error regardless of the policy.
+///
+/// # Example
+/// ```no_run
+/// use dlsite::{client::unknown_field::UnknownFieldPolicy, DlsiteClient};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let client = DlsiteClient::builder()
+///         .unknown_field_policy(UnknownFieldPolicy::Collect)
+///         .build()
+///         .unwrap();
+///     client.product_api().get(&"RJ01014447".parse().unwrap()).await.unwrap();
+///     for report in client.take_unknown_field_reports() {
+///         println!("{}: {:?}", report.url, report.paths);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum UnknownFieldPolicy {
+    /// Ignore unknown fields. They are still logged with `unknown-field-log` feature.
+    #[default]
+    Ignore,
+    /// Keep reports of unknown fields in the client. Get them by
+    /// [`DlsiteClient::take_unknown_field_reports`](super::DlsiteClient::take_unknown_field_reports).
+    ///
+    /// Each unknown field is reported once per client, even if it is found in responses for other
+    /// products, and at most [`MAX_UNKNOWN_FIELD_REPORTS`] reports are kept until they are taken.
+    Collect,
+    /// Fail with [`DlsiteError::Parse`](crate::DlsiteError::Parse) whose field is the unknown paths.
+    Fail,
+}
+
+/// Unknown fields found in a response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFieldReport {
+    pub endpoint: Endpoint,
+    pub url: String,
+    /// ID of the product requested.
+    pub id: Option<String>,
+    /// Paths of the unknown fields, like `0.new_field` or `RJ01014447.foo.bar`.
+    pub paths: Vec<String>,
+}
+
+/// Maximum number of reports kept by [`UnknownFieldPolicy::Collect`]. Later reports are dropped
+/// until the reports are taken.
+pub const MAX_UNKNOWN_FIELD_REPORTS: usize = 100;
+
+/// Reports kept by [`UnknownFieldPolicy::Collect`].
+#[derive(Debug, Default)]
+pub(crate) struct ReportBuffer {
+    reports: Vec<UnknownFieldReport>,
+    /// Paths already reported, normalized by [`normalize_path`].
+    seen: HashSet<(Endpoint, String)>,
+}
+
+impl ReportBuffer {
+    /// Keep `report` without paths already reported. Nothing is kept if no path is new or the
+    /// buffer is full.
+    pub(crate) fn push(&mut self, mut report: UnknownFieldReport) {
+        if self.reports.len() >= MAX_UNKNOWN_FIELD_REPORTS {
+            return;
+        }
+        report
+            .paths
+            .retain(|path| self.seen.insert((report.endpoint, normalize_path(path))));
+        if !report.paths.is_empty() {
+            self.reports.push(report);
+        }
+    }
+
+    pub(crate) fn take(&mut self) -> Vec<UnknownFieldReport> {
+        std::mem::take(&mut self.reports)
+    }
+}
+
+/// Replace product IDs and array indices in a path with `*`, so that the same field of other
+/// products or items has the same path.
+fn normalize_path(path: &str) -> String {
+    path.split('.')
+        .map(|segment| {
+            if segment.bytes().all(|b| b.is_ascii_digit()) || segment.parse::<ProductId>().is_ok() {
+                "*"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Deserialize a value, and return it with paths of the unknown fields.
+///
+/// On failure, the path where the error happened is set to [`ParseError::field`].
+pub(crate) fn deserialize<'de, T, D>(deserializer: D) -> Result<(T, Vec<String>)>
+where
+    T: DeserializeOwned,
+    D: Deserializer<'de>,
+    D::Error: std::error::Error + Send + Sync + 'static,
+{
+    let mut unknown = vec![];
+    let mut track = serde_path_to_error::Track::new();
+    let result = serde_ignored::deserialize(
+        serde_path_to_error::Deserializer::new(deserializer, &mut track),
+        |path| unknown.push(path.to_string()),
+    );
+    match result {
+        Ok(value) => Ok((value, unknown)),
+        Err(e) => Err(ParseError::new("Failed to parse json")
+            .field(&track.path().to_string())
+            .source(e)
+            .into()),
+    }
+}
+
+/// Deserialize a JSON string. See [`deserialize`].
+pub(crate) fn from_json<T: DeserializeOwned>(json: &str) -> Result<(T, Vec<String>)> {
+    deserialize(&mut serde_json::Deserializer::from_str(json))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::{from_json, normalize_path, UnknownFieldPolicy};
+    use crate::{
+        client::{
+            product::review::{ProductReview, ReviewQuery},
+            stub::{StubResponse, StubServer},
+            Endpoint,
+        },
+        DlsiteClient, DlsiteError,
+    };
+
+    #[derive(Debug, Deserialize)]
+    struct Item {
+        #[allow(dead_code)]
+        name: String,
+    }
+
+    #[test]
+    fn unknown_paths() {
+        let (_, unknown) =
+            from_json::<Vec<Item>>(r#"[{"name": "a", "x": 1}, {"name": "b", "y": {"z": 2}}]"#)
+                .unwrap();
+        assert_eq!(unknown, vec!["0.x", "1.y"]);
+    }
+
+    #[test]
+    fn normalized_path() {
+        assert_eq!(normalize_path("RJ01014447.foo.3.bar"), "*.foo.*.bar");
+        assert_eq!(normalize_path("review_list.0.x"), "review_list.*.x");
+    }
+
+    #[test]
+    fn error_path() {
+        let Err(DlsiteError::Parse(e)) = from_json::<Vec<Item>>(r#"[{"name": "a"}, {"name": 1}]"#)
+        else {
+            panic!("Expected parse error");
+        };
+        assert_eq!(e.field.as_deref(), Some("[1].name"));
+    }
+
+    const REVIEW_JSON: &str = r#"{"is_success": true, "error_msg": "", "review_list": [],
+        "reviewer_genre_list": null, "new_field": 1}"#;
+
+    async fn get_review(
+        server: &StubServer,
+        policy: UnknownFieldPolicy,
+    ) -> (DlsiteClient, crate::error::Result<ProductReview>) {
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .unknown_field_policy(policy)
+            .build()
+            .unwrap();
+        let res = client
+            .product()
+            .get_review(&"RJ01014447".parse().unwrap(), &ReviewQuery::default())
+            .await;
+        (client, res)
+    }
+
+    #[tokio::test]
+    async fn unknown_field_policy() {
+        let server = StubServer::start(vec![StubResponse::ok(REVIEW_JSON); 3]).await;
+
+        let (client, res) = get_review(&server, UnknownFieldPolicy::Ignore).await;
+        assert!(res.is_ok());
+        assert!(client.take_unknown_field_reports().is_empty());
+
+        let (client, res) = get_review(&server, UnknownFieldPolicy::Collect).await;
+        assert!(res.is_ok());
+        let reports = client.take_unknown_field_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].endpoint, Endpoint::Review);
+        assert_eq!(reports[0].id.as_deref(), Some("RJ01014447"));
+        assert_eq!(reports[0].paths, vec!["new_field"]);
+        assert!(client.take_unknown_field_reports().is_empty());
+
+        let (_, res) = get_review(&server, UnknownFieldPolicy::Fail).await;
+        let Err(DlsiteError::Parse(e)) = res else {
+            panic!("Expected parse error");
+        };
+        assert_eq!(e.field.as_deref(), Some("new_field"));
+        assert_eq!(e.endpoint, Some(Endpoint::Review));
+    }
+
+    #[tokio::test]
+    async fn collect_once_per_path() {
+        let other = REVIEW_JSON.replace("new_field", "other_field");
+        let server = StubServer::start(vec![
+            StubResponse::ok(REVIEW_JSON),
+            StubResponse::ok(REVIEW_JSON),
+            StubResponse::ok(&other),
+        ])
+        .await;
+        let client = DlsiteClient::builder()
+            .base_url(&server.url())
+            .unknown_field_policy(UnknownFieldPolicy::Collect)
+            .build()
+            .unwrap();
+        for _ in 0..3 {
+            client
+                .product()
+                .get_review(&"RJ01014447".parse().unwrap(), &ReviewQuery::default())
+                .await
+                .unwrap();
+        }
+        let reports = client.take_unknown_field_reports();
+        let paths: Vec<_> = reports.iter().map(|r| r.paths.clone()).collect();
+        assert_eq!(paths, vec![vec!["new_field"], vec!["other_field"]]);
+    }
+}
@@ -1,5 +1,9 @@
+use std::{fmt, path::PathBuf, time::Duration};
+
 use thiserror::Error;
 
+use crate::client::Endpoint;
+
 /// Errors that can occur while using the Dlsite API
 #[derive(Debug, Error)]
 pub enum DlsiteError {
@@ -7,10 +11,142 @@ pub enum DlsiteError {
     Reqwest(#[from] reqwest::Error),
     #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
-    #[error("{0}")]
-    Parse(String),
+    #[error(transparent)]
+    Parse(Box<ParseError>),
     #[error("{0}")]
     Server(String),
+    /// The product or circle does not exist.
+    #[error("{id} not found")]
+    NotFound { id: String },
+    /// DLsite refused the request because of too many requests.
+    #[error("Rate limited by DLsite (retry after {retry_after:?})")]
+    RateLimited { retry_after: Option<Duration> },
+    /// DLsite responded with an unexpected HTTP status.
+    #[error("HTTP status {code} for {url}")]
+    HttpStatus { code: u16, url: String },
+    /// DLsite is under maintenance.
+    #[error("DLsite is under maintenance")]
+    Maintenance,
+    /// DLsite responded with an age verification page instead of the content.
+    #[error("Age verification is required")]
+    AgeGate,
+    /// The string is not a valid product or maker ID.
+    #[error("{0}")]
+    InvalidId(String),
+    /// The string is not a DLsite URL which has a product or maker ID.
+    #[error("{0}")]
+    InvalidUrl(String),
+    /// Invalid configuration given to [`DlsiteClientBuilder`](crate::client::builder::DlsiteClientBuilder).
+    #[error("{0}")]
+    Config(String),
+    /// Error raised by a custom [`Transport`](crate::client::transport::Transport).
+    #[error(transparent)]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Failure to parse a response from DLsite.
+///
+/// Besides the message, this holds where the failure happened as much as known.
+#[derive(Debug, Default)]
+pub struct ParseError {
+    pub message: String,
+    /// Endpoint of the response.
+    pub endpoint: Option<Endpoint>,
+    /// URL of the response.
+    pub url: Option<String>,
+    /// ID of the product, circle or reviewer requested.
+    pub id: Option<String>,
+    /// Field or selector which failed to be parsed.
+    pub field: Option<String>,
+    /// Truncated response body.
+    pub snippet: Option<String>,
+    /// Path where the whole response body was dumped. See
+    /// [`DlsiteClientBuilder::dump_parse_failures`](crate::client::builder::DlsiteClientBuilder::dump_parse_failures).
+    pub dump_path: Option<PathBuf>,
+    pub source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+/// Max length of [`ParseError::snippet`] in chars.
+const SNIPPET_LEN: usize = 500;
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn field(mut self, field: &str) -> Self {
+        self.field = Some(field.to_string());
+        self
+    }
+
+    pub(crate) fn source(
+        mut self,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub(crate) fn set_snippet(&mut self, body: &str) {
+        let mut snippet: String = body.chars().take(SNIPPET_LEN).collect();
+        if snippet.len() < body.len() {
+            snippet.push_str("...");
+        }
+        self.snippet = Some(snippet);
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(field) = &self.field {
+            write!(f, " (field: {field})")?;
+        }
+        if let Some(id) = &self.id {
+            write!(f, " (id: {id})")?;
+        }
+        if let Some(url) = &self.url {
+            write!(f, " (url: {url})")?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, ": {source}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|e| e.as_ref() as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl From<ParseError> for DlsiteError {
+    fn from(e: ParseError) -> Self {
+        DlsiteError::Parse(Box::new(e))
+    }
+}
+
+impl DlsiteError {
+    /// Create a [`DlsiteError::Parse`] with a message.
+    pub(crate) fn parse(message: impl Into<String>) -> Self {
+        ParseError::new(message).into()
+    }
+
+    /// Convert 404 status error to [`DlsiteError::NotFound`] with `id`.
+    pub(crate) fn with_not_found_id(self, id: &str) -> Self {
+        match self {
+            DlsiteError::HttpStatus { code: 404, .. } => {
+                DlsiteError::NotFound { id: id.to_string() }
+            }
+            e => e,
+        }
+    }
 }
 
 pub(crate) type Result<T> = std::result::Result<T, DlsiteError>;
@@ -0,0 +1,210 @@
+//! IDs of products and makers (circles).
+
+use std::{fmt, str::FromStr};
+
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+
+use super::site::Site;
+use crate::DlsiteError;
+
+macro_rules! define_id {
+    (
+        $(#[$meta:meta])* $name:ident, $kind:literal, $digits:pat,
+        [$($prefix:literal => $site:expr),+ $(,)?]
+    ) => {
+        $(#[$meta])*
+        #[derive(
+            Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, SerializeDisplay, DeserializeFromStr,
+        )]
+        pub struct $name(String);
+
+        impl $name {
+            /// Prefixes accepted as this ID.
+            pub const PREFIXES: &'static [&'static str] = &[$($prefix),+];
+
+            /// The normalized (uppercase) ID.
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// Two-letter prefix of the ID, like `RJ`.
+            pub fn prefix(&self) -> &str {
+                &self.0[..2]
+            }
+
+            /// Number part of the ID.
+            pub fn number(&self) -> &str {
+                &self.0[2..]
+            }
+
+            /// Site implied by the prefix.
+            ///
+            /// Prefixes are shared by the R18 site and its all-ages counterpart, so this returns the
+            /// R18 one (e.g. [`Site::Maniax`] for `RJ` even if the product is on [`Site::Home`]).
+            pub fn site(&self) -> Site {
+                match self.prefix() {
+                    $($prefix => $site,)+
+                    _ => unreachable!("ID is validated on parse"),
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = DlsiteError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let id = s.trim().to_ascii_uppercase();
+                let (prefix, number) = id.split_at_checked(2).ok_or_else(|| invalid(s, $kind))?;
+                if !Self::PREFIXES.contains(&prefix)
+                    || !matches!(number.len(), $digits)
+                    || !number.bytes().all(|b| b.is_ascii_digit())
+                {
+                    return Err(invalid(s, $kind));
+                }
+                Ok(Self(id))
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = DlsiteError;
+
+            fn try_from(s: &str) -> Result<Self, Self::Error> {
+                s.parse()
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = DlsiteError;
+
+            fn try_from(s: String) -> Result<Self, Self::Error> {
+                s.parse()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+    };
+}
+
+fn invalid(s: &str, kind: &str) -> DlsiteError {
+    DlsiteError::InvalidId(format!("{s:?} is not a valid {kind} ID"))
+}
+
+define_id!(
+    /// ID of a product (work), like `RJ01014447`.
+    ///
+    /// Parsing is case-insensitive and accepts both 6-digit and 8-digit forms. Valid prefixes are
+    /// `RJ`, `RE` (doujin), `VJ` (software) and `BJ` (books).
+    ///
+    /// # Example
+    /// ```
+    /// use dlsite::interface::{id::ProductId, site::Site};
+    ///
+    /// let id: ProductId = "rj01014447".parse().unwrap();
+    /// assert_eq!(id, "RJ01014447");
+    /// assert_eq!(id.site(), Site::Maniax);
+    /// assert!("RG01014447".parse::<ProductId>().is_err());
+    /// ```
+    ProductId,
+    "product",
+    6 | 8,
+    ["RJ" => Site::Maniax, "RE" => Site::Maniax, "VJ" => Site::Pro, "BJ" => Site::Books]
+);
+
+define_id!(
+    /// ID of a maker (circle, brand or publisher), like `RG24350`.
+    ///
+    /// Parsing is case-insensitive and accepts 6-digit and 8-digit forms, as well as 5-digit form
+    /// used by old makers. Valid prefixes are `RG` (doujin), `VG` (software) and `BG` (books).
+    MakerId,
+    "maker",
+    5 | 6 | 8,
+    ["RG" => Site::Maniax, "VG" => Site::Pro, "BG" => Site::Books]
+);
+
+/// ID of a circle. Circles are makers of doujin works.
+pub type CircleId = MakerId;
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{MakerId, ProductId};
+    use crate::interface::site::Site;
+
+    #[test_case("RJ123456", "RJ123456", Site::Maniax; "6 digits")]
+    #[test_case("RJ01014447", "RJ01014447", Site::Maniax; "8 digits")]
+    #[test_case("rj01014447", "RJ01014447", Site::Maniax; "lowercase")]
+    #[test_case(" RE123456\n", "RE123456", Site::Maniax; "whitespace")]
+    #[test_case("VJ01000001", "VJ01000001", Site::Pro; "software")]
+    #[test_case("bj123456", "BJ123456", Site::Books; "books")]
+    fn product_id_valid(input: &str, expected: &str, site: Site) {
+        let id: ProductId = input.parse().unwrap();
+        assert_eq!(id.as_str(), expected);
+        assert_eq!(id.site(), site);
+    }
+
+    #[test_case(""; "empty")]
+    #[test_case("R"; "too short")]
+    #[test_case("RJ12345"; "5 digits")]
+    #[test_case("RJ1234567"; "7 digits")]
+    #[test_case("RJ12345a"; "not digit")]
+    #[test_case("RG123456"; "maker prefix")]
+    #[test_case("XX123456"; "unknown prefix")]
+    #[test_case("ＲJ123456"; "non ascii")]
+    fn product_id_invalid(input: &str) {
+        assert!(input.parse::<ProductId>().is_err());
+    }
+
+    #[test]
+    fn maker_id() {
+        let id: MakerId = "rg24350".parse().unwrap();
+        assert_eq!(id, "RG24350");
+        assert_eq!(id.prefix(), "RG");
+        assert_eq!(id.number(), "24350");
+        assert_eq!(id.site(), Site::Maniax);
+        assert!("RG01047541".parse::<MakerId>().is_ok());
+        assert!("RG1234".parse::<MakerId>().is_err());
+        assert!("RJ24350".parse::<MakerId>().is_err());
+    }
+
+    #[test]
+    fn id_serde() {
+        let id: ProductId = serde_json::from_str("\"rj01014447\"").unwrap();
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"RJ01014447\"");
+        assert!(serde_json::from_str::<ProductId>("\"foo\"").is_err());
+    }
+}
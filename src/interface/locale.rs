@@ -0,0 +1,22 @@
+use strum::{Display, EnumString};
+
+/// Locale of DLsite pages and APIs.
+#[derive(Display, EnumString, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    /// 日本語
+    #[default]
+    #[strum(serialize = "ja_JP")]
+    Japanese,
+    /// English
+    #[strum(serialize = "en_US")]
+    English,
+    /// 简体中文
+    #[strum(serialize = "zh_CN")]
+    ChineseSimplified,
+    /// 繁體中文
+    #[strum(serialize = "zh_TW")]
+    ChineseTraditional,
+    /// 한국어
+    #[strum(serialize = "ko_KR")]
+    Korean,
+}
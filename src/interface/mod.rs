@@ -1,7 +1,11 @@
 //! Common interfaces
 
+pub mod id;
+pub mod locale;
 pub mod product;
 pub mod query;
+pub mod site;
+pub mod url;
 pub mod genre {
     //! Interfaces related to genre.
 
@@ -1,13 +1,32 @@
 use strum::Display;
 
-#[derive(Display, Default)]
+use super::locale::Locale;
+
+/// Display language of search results.
+#[derive(Display, Default, Clone)]
 #[strum(serialize_all = "snake_case")]
 pub enum Language {
     #[default]
     Jp,
+    En,
+    ZhCn,
+    ZhTw,
+    Ko,
+}
+
+impl From<Locale> for Language {
+    fn from(locale: Locale) -> Self {
+        match locale {
+            Locale::Japanese => Language::Jp,
+            Locale::English => Language::En,
+            Locale::ChineseSimplified => Language::ZhCn,
+            Locale::ChineseTraditional => Language::ZhTw,
+            Locale::Korean => Language::Ko,
+        }
+    }
 }
 
-#[derive(Display)]
+#[derive(Display, Clone)]
 #[strum(serialize_all = "snake_case")]
 pub enum SexCategory {
     Male,
@@ -15,7 +34,7 @@ pub enum SexCategory {
 }
 
 /// Flag to represent sales status
-#[derive(Display)]
+#[derive(Display, Clone)]
 #[strum(serialize_all = "snake_case")]
 pub enum AnaFlg {
     Off,
@@ -24,7 +43,7 @@ pub enum AnaFlg {
     All,
 }
 
-#[derive(Display)]
+#[derive(Display, Clone)]
 #[strum(serialize_all = "snake_case")]
 pub enum Order {
     Trend,
@@ -46,14 +65,14 @@ pub enum Order {
     ReviewD,
 }
 
-#[derive(Display)]
+#[derive(Display, Clone)]
 #[strum(serialize_all = "snake_case")]
 pub enum OptionAndOr {
     And,
     Or,
 }
 
-#[derive(Display)]
+#[derive(Display, Clone)]
 pub enum ReleaseTerm {
     None,
     Week,
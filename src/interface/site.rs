@@ -0,0 +1,72 @@
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use strum::{Display, EnumString};
+
+/// Site (floor) of DLsite. The value is the path segment used in URLs, like `maniax` in
+/// `https://www.dlsite.com/maniax/`.
+///
+/// To send requests to a certain site, see [`DlsiteClient::site`](crate::DlsiteClient::site).
+#[derive(
+    Display,
+    EnumString,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    SerializeDisplay,
+    DeserializeFromStr,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Site {
+    /// 同人 (R18)
+    #[default]
+    Maniax,
+    /// 同人 (全年齢)
+    Home,
+    /// 乙女
+    Girls,
+    /// BL
+    Bl,
+    /// 乙女ドラマCD
+    GirlsDrama,
+    /// BLドラマCD
+    BlDrama,
+    /// スマホゲーム・アプリ
+    Appx,
+    /// 美少女ゲーム (R18)
+    Pro,
+    /// PCソフト (全年齢)
+    Soft,
+    /// 乙女向け商業ゲーム
+    GirlsPro,
+    /// BL商業ゲーム
+    BlPro,
+    /// 成年コミック (R18)
+    Books,
+    /// コミック (全年齢)
+    Comic,
+}
+
+impl Site {
+    /// Prefix of product IDs on this site, which is `RJ`, `VJ` or `BJ`.
+    pub fn id_prefix(&self) -> &'static str {
+        match self {
+            Site::Maniax
+            | Site::Home
+            | Site::Girls
+            | Site::Bl
+            | Site::GirlsDrama
+            | Site::BlDrama
+            | Site::Appx => "RJ",
+            Site::Pro | Site::Soft | Site::GirlsPro | Site::BlPro => "VJ",
+            Site::Books | Site::Comic => "BJ",
+        }
+    }
+
+    /// Whether the site shares ID prefixes with `other`, e.g. [`Site::Maniax`] and [`Site::Home`].
+    pub fn shares_ids_with(&self, other: Site) -> bool {
+        self.id_prefix() == other.id_prefix()
+    }
+}
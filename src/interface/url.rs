@@ -0,0 +1,267 @@
+//! Parse DLsite URLs into IDs, and build canonical URLs. For more information, see [`DlsiteUrl`].
+
+use std::{fmt, str::FromStr};
+
+use super::{
+    id::{MakerId, ProductId},
+    site::Site,
+};
+use crate::{client::search::SearchProductQuery, error::Result, DlsiteError};
+
+const ORIGIN: &str = "https://www.dlsite.com";
+
+/// Kind of a DLsite page which has an ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageKind {
+    /// Product page (`/work/`).
+    Work,
+    /// Product page before release (`/announce/`).
+    Announce,
+    /// Circle (maker) profile page (`/circle/profile/`).
+    Circle,
+}
+
+/// ID found in a DLsite URL.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DlsiteId {
+    Product(ProductId),
+    Maker(MakerId),
+}
+
+/// DLsite page URL parsed into its site, kind and ID.
+///
+/// Accepted URLs are pages on `www.dlsite.com` (including `-touch` sites, relative and
+/// scheme-less URLs) and `dlsite.jp` short links. As short links don't tell the site, the site of
+/// them is inferred from the ID (see [`ProductId::site`]).
+///
+/// # Example
+/// ```
+/// use dlsite::interface::{site::Site, url::{DlsiteUrl, PageKind}};
+///
+/// let url: DlsiteUrl = "https://www.dlsite.com/home-touch/work/=/product_id/RJ01014447.html"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(url.site, Site::Home);
+/// assert_eq!(url.kind, PageKind::Work);
+/// assert_eq!(url.product_id().unwrap(), "RJ01014447");
+/// assert_eq!(
+///     url.to_string(),
+///     "https://www.dlsite.com/home/work/=/product_id/RJ01014447.html"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DlsiteUrl {
+    pub site: Site,
+    pub kind: PageKind,
+    pub id: DlsiteId,
+}
+
+impl DlsiteUrl {
+    /// Parse a URL of a product page or a circle profile page.
+    pub fn parse(url: &str) -> Result<Self> {
+        let invalid = |msg: &str| DlsiteError::InvalidUrl(format!("{msg}: {url}"));
+
+        let input = url.trim();
+        let input = if input.contains("://") || input.starts_with('/') {
+            input.to_string()
+        } else {
+            format!("https://{input}")
+        };
+        let base = ::url::Url::parse(ORIGIN).unwrap();
+        let parsed = ::url::Url::options()
+            .base_url(Some(&base))
+            .parse(&input)
+            .map_err(|_| invalid("Invalid URL"))?;
+        let host = parsed.host_str().unwrap_or_default();
+        let segments: Vec<&str> = parsed
+            .path_segments()
+            .map(|s| s.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+
+        if host == "dlsite.jp" || host.ends_with(".dlsite.jp") {
+            let last = segments
+                .last()
+                .ok_or_else(|| invalid("No ID in short link"))?;
+            let id = last.trim_end_matches(".html");
+            return if let Ok(id) = id.parse::<ProductId>() {
+                Ok(Self {
+                    site: id.site(),
+                    kind: PageKind::Work,
+                    id: DlsiteId::Product(id),
+                })
+            } else if let Ok(id) = id.parse::<MakerId>() {
+                Ok(Self {
+                    site: id.site(),
+                    kind: PageKind::Circle,
+                    id: DlsiteId::Maker(id),
+                })
+            } else {
+                Err(invalid("No ID in short link"))
+            };
+        }
+        if host != "dlsite.com" && !host.ends_with(".dlsite.com") {
+            return Err(invalid("Not a DLsite URL"));
+        }
+
+        let site = segments
+            .first()
+            .and_then(|s| s.trim_end_matches("-touch").parse::<Site>().ok())
+            .ok_or_else(|| invalid("Unknown site"))?;
+        let (kind, id) = match segments.get(1..3) {
+            Some(["work", "="]) => (PageKind::Work, product_param(&segments)),
+            Some(["announce", "="]) => (PageKind::Announce, product_param(&segments)),
+            Some(["circle", "profile"]) => (
+                PageKind::Circle,
+                path_param(&segments, "maker_id")
+                    .and_then(|id| id.parse().ok())
+                    .map(DlsiteId::Maker),
+            ),
+            _ => return Err(invalid("Unknown page")),
+        };
+        let id = id.ok_or_else(|| invalid("No valid ID"))?;
+
+        Ok(Self { site, kind, id })
+    }
+
+    /// Product ID if this is a product page.
+    pub fn product_id(&self) -> Option<&ProductId> {
+        match &self.id {
+            DlsiteId::Product(id) => Some(id),
+            DlsiteId::Maker(_) => None,
+        }
+    }
+
+    /// Maker ID if this is a circle profile page.
+    pub fn maker_id(&self) -> Option<&MakerId> {
+        match &self.id {
+            DlsiteId::Maker(id) => Some(id),
+            DlsiteId::Product(_) => None,
+        }
+    }
+
+    /// Canonical URL of the page.
+    pub fn to_url(&self) -> String {
+        match (&self.kind, &self.id) {
+            (PageKind::Announce, DlsiteId::Product(id)) => {
+                format!("{ORIGIN}/{}/announce/=/product_id/{id}.html", self.site)
+            }
+            (_, DlsiteId::Product(id)) => product_url(self.site, id),
+            (_, DlsiteId::Maker(id)) => circle_url(self.site, id),
+        }
+    }
+}
+
+impl FromStr for DlsiteUrl {
+    type Err = DlsiteError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for DlsiteUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_url())
+    }
+}
+
+fn product_param(segments: &[&str]) -> Option<DlsiteId> {
+    path_param(segments, "product_id")
+        .and_then(|id| id.parse().ok())
+        .map(DlsiteId::Product)
+}
+
+/// Get the value of `key` in DLsite style path parameters (`/=/key/value/key2/value2`).
+/// `.html` suffix of the value is removed.
+pub(crate) fn path_param<'a>(segments: &[&'a str], key: &str) -> Option<&'a str> {
+    let pos = segments.iter().position(|s| *s == key)?;
+    Some(segments.get(pos + 1)?.trim_end_matches(".html"))
+}
+
+/// Canonical URL of a product page.
+pub fn product_url(site: Site, id: &ProductId) -> String {
+    format!("{ORIGIN}/{site}/work/=/product_id/{id}.html")
+}
+
+/// Canonical URL of a circle profile page.
+pub fn circle_url(site: Site, id: &MakerId) -> String {
+    format!("{ORIGIN}/{site}/circle/profile/=/maker_id/{id}.html")
+}
+
+/// URL of the search page for `query`.
+pub fn search_url(site: Site, query: &SearchProductQuery) -> String {
+    let path = query.to_path();
+    let params = path.trim_start_matches("/fsr/ajax/=");
+    format!("{ORIGIN}/{site}/fsr/={params}")
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{circle_url, path_param, search_url, DlsiteUrl, PageKind};
+    use crate::{client::search::SearchProductQuery, interface::site::Site};
+
+    #[test_case("https://www.dlsite.com/maniax/work/=/product_id/RJ01014447.html", Site::Maniax, PageKind::Work, "RJ01014447"; "work")]
+    #[test_case("https://www.dlsite.com/maniax/work/=/product_id/RJ01014447.html/?locale=en_US", Site::Maniax, PageKind::Work, "RJ01014447"; "query")]
+    #[test_case("https://www.dlsite.com/home/announce/=/product_id/RJ01100000.html", Site::Home, PageKind::Announce, "RJ01100000"; "announce")]
+    #[test_case("https://www.dlsite.com/maniax-touch/work/=/product_id/rj403038.html", Site::Maniax, PageKind::Work, "RJ403038"; "touch")]
+    #[test_case("www.dlsite.com/pro/work/=/product_id/VJ01000513.html", Site::Pro, PageKind::Work, "VJ01000513"; "no scheme")]
+    #[test_case("//www.dlsite.com/books/work/=/product_id/BJ123456.html", Site::Books, PageKind::Work, "BJ123456"; "protocol relative")]
+    #[test_case("/girls/work/=/product_id/RJ01084246.html", Site::Girls, PageKind::Work, "RJ01084246"; "relative")]
+    #[test_case("https://dlsite.jp/mawtw/RJ01014447", Site::Maniax, PageKind::Work, "RJ01014447"; "short link")]
+    #[test_case("https://www.dlsite.com/maniax/circle/profile/=/maker_id/RG24350.html", Site::Maniax, PageKind::Circle, "RG24350"; "circle")]
+    #[test_case("https://www.dlsite.com/maniax/circle/profile/=/show_type/3/maker_id/RG24350.html/order/price", Site::Maniax, PageKind::Circle, "RG24350"; "circle with params")]
+    fn parse_url(url: &str, site: Site, kind: PageKind, id: &str) {
+        let url = DlsiteUrl::parse(url).unwrap();
+        assert_eq!(url.site, site);
+        assert_eq!(url.kind, kind);
+        match kind {
+            PageKind::Circle => assert_eq!(url.maker_id().unwrap(), id),
+            _ => assert_eq!(url.product_id().unwrap(), id),
+        }
+    }
+
+    #[test_case("https://example.com/maniax/work/=/product_id/RJ01014447.html"; "other host")]
+    #[test_case("https://www.dlsite.com/unknown/work/=/product_id/RJ01014447.html"; "unknown site")]
+    #[test_case("https://www.dlsite.com/maniax/fsr/=/keyword/a"; "search")]
+    #[test_case("https://www.dlsite.com/maniax/work/=/product_id/RG24350.html"; "maker id in work")]
+    #[test_case("https://dlsite.jp/mawtw/"; "short link without id")]
+    fn parse_url_invalid(url: &str) {
+        assert!(DlsiteUrl::parse(url).is_err());
+    }
+
+    #[test]
+    fn build_url() {
+        let url: DlsiteUrl =
+            "https://www.dlsite.com/home-touch/announce/=/product_id/RJ01100000.html"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            url.to_url(),
+            "https://www.dlsite.com/home/announce/=/product_id/RJ01100000.html"
+        );
+        assert_eq!(
+            circle_url(Site::Maniax, &"rg24350".parse().unwrap()),
+            "https://www.dlsite.com/maniax/circle/profile/=/maker_id/RG24350.html"
+        );
+        assert_eq!(
+            search_url(
+                Site::Maniax,
+                &SearchProductQuery {
+                    keyword: Some("ASMR".to_string()),
+                    ..Default::default()
+                }
+            ),
+            "https://www.dlsite.com/maniax/fsr/=/language/jp/keyword/ASMR"
+        );
+    }
+
+    #[test]
+    fn path_params() {
+        let segments = ["maniax", "fsr", "=", "genre", "497", "from", "work.genre"];
+        assert_eq!(path_param(&segments, "genre"), Some("497"));
+        assert_eq!(path_param(&segments, "from"), Some("work.genre"));
+        assert_eq!(path_param(&segments, "page"), None);
+    }
+}
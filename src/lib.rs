@@ -9,6 +9,7 @@
 pub mod client;
 pub mod error;
 pub mod interface;
+pub mod parse;
 mod utils;
 
 pub use client::DlsiteClient;
@@ -0,0 +1,198 @@
+//! Parse pages and API responses which were fetched without [`DlsiteClient`](crate::DlsiteClient),
+//! e.g. archived pages or responses from your own crawler.
+//!
+//! These are the same parsers the clients use, so the results are the same types. Unknown fields
+//! in JSON are ignored, and errors don't have the request context (URL, endpoint, ID) because
+//! there's no request.
+//!
+//! # Example
+//! ```no_run
+//! use dlsite::{client::ParseMode, interface::locale::Locale, parse};
+//!
+//! let html = std::fs::read_to_string("RJ01014447.html").unwrap();
+//! let product = parse::product_html(&html, Locale::Japanese, ParseMode::Lenient).unwrap();
+//! println!("{}", product.circle_name);
+//! ```
+
+use std::collections::HashMap;
+
+use scraper::Html;
+
+use crate::{
+    client::{
+        circle::{
+            parse_circle_html, parse_circle_profile, parse_circle_sales, CircleProfile,
+            CircleSaleItem,
+        },
+        product::{
+            ajax::{parse_ajax_json, ProductAjax},
+            html::{parse_product_html, ProductHtml},
+            review::{parse_review_json, ProductReview},
+        },
+        product_api::interface::ProductApiContent,
+        ranking::{parse_ranking_html, RankingEntry},
+        search::{parse_search_ajax, SearchResult},
+        unknown_field, ParseMode,
+    },
+    error::{ParseError, Result},
+    interface::{id::ProductId, locale::Locale},
+};
+
+/// Parse a product page (`/work/=/product_id/...`) shown in `locale`.
+///
+/// This is what [`ProductClient::get_html`](crate::client::product::ProductClient::get_html)
+/// returns.
+pub fn product_html(html: &str, locale: Locale, mode: ParseMode) -> Result<ProductHtml> {
+    parse_product_html(&Html::parse_document(html), locale, mode)
+}
+
+/// Parse a response of the search ajax API (`/fsr/ajax/=/...`).
+///
+/// This is what [`SearchClient::search_product`](crate::client::search::SearchClient::search_product)
+/// returns, except that `query_path` is empty because there's no request.
+pub fn search_ajax(json: &str) -> Result<SearchResult> {
+    let (products, count) = parse_search_ajax(json)?;
+    Ok(SearchResult {
+        products,
+        count,
+        query_path: String::new(),
+    })
+}
+
+/// Parse a circle profile page (`/circle/profile/=/maker_id/...`).
+///
+/// This is what [`CircleClient::get_circle`](crate::client::circle::CircleClient::get_circle)
+/// returns, except that `query_path` is empty because there's no request.
+pub fn circle_html(html: &str) -> Result<SearchResult> {
+    let (products, count) = parse_circle_html(html)?;
+    Ok(SearchResult {
+        products,
+        count,
+        query_path: String::new(),
+    })
+}
+
+/// Parse the profile in the header of a circle page (`/circle/profile/=/maker_id/...`).
+pub fn circle_profile_html(html: &str) -> Result<CircleProfile> {
+    parse_circle_profile(html)
+}
+
+/// Parse works on discount or point-up campaign in a circle page. Unlike
+/// [`CircleClient::get_sales`](crate::client::circle::CircleClient::get_sales), only the works in
+/// this page are returned.
+pub fn circle_sales_html(html: &str) -> Result<Vec<CircleSaleItem>> {
+    parse_circle_sales(html).map(|(items, _)| items)
+}
+
+/// Parse a ranking page (`/ranking/...`).
+pub fn ranking_html(html: &str) -> Result<Vec<RankingEntry>> {
+    parse_ranking_html(html)
+}
+
+/// Parse a response of the ajax product info API (`/product/info/ajax?product_id=...`).
+pub fn ajax_json(json: &str) -> Result<HashMap<ProductId, ProductAjax>> {
+    parse_ajax_json(json).map(|(products, _)| products)
+}
+
+/// Parse a response of the review API (`/api/review?...`).
+pub fn review_json(json: &str) -> Result<ProductReview> {
+    parse_review_json(json).map(|(review, _)| review)
+}
+
+/// Parse a response of the product API (`/api/=/product.json?workno=...`).
+///
+/// This is what [`ProductApiClient::get`](crate::client::product_api::ProductApiClient::get)
+/// returns. The API returns an empty array if the product is not found, which is an error here.
+pub fn product_json(json: &str) -> Result<ProductApiContent> {
+    let (products, _) = unknown_field::from_json::<Vec<ProductApiContent>>(json)?;
+    products
+        .into_iter()
+        .next()
+        .ok_or_else(|| ParseError::new("No product in the response").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::{
+        client::{cassette::recorded_body, ParseMode},
+        interface::locale::Locale,
+        DlsiteError,
+    };
+
+    #[test]
+    fn parse_json() {
+        assert!(super::ajax_json("[]").unwrap().is_empty());
+        assert!(super::product_json("[]").is_err());
+
+        let review = super::review_json(
+            r#"{"is_success": true, "error_msg": "", "review_list": [], "reviewer_genre_list": null}"#,
+        )
+        .unwrap();
+        assert!(review.review_list.is_empty());
+
+        let result =
+            super::search_ajax(r#"{"search_result": "", "page_info": {"count": 0}}"#).unwrap();
+        assert!(result.products.is_empty());
+        assert_eq!(result.count, 0);
+    }
+
+    #[test]
+    fn parse_error() {
+        let Err(DlsiteError::Parse(e)) =
+            super::product_html("<html></html>", Locale::Japanese, ParseMode::Lenient)
+        else {
+            panic!("Expected parse error");
+        };
+        assert!(e.url.is_none());
+        assert!(super::circle_html("<html></html>").is_err());
+        assert!(super::circle_profile_html("<html></html>").is_err());
+    }
+
+    // The tests below parse responses recorded for the client tests.
+
+    #[test]
+    fn parse_recorded_product() {
+        let name = "product/get_product_1_content";
+        let id = "RJ403038".parse().unwrap();
+
+        let html = recorded_body(name, "/work/=/product_id/RJ403038");
+        let product = super::product_html(&html, Locale::Japanese, ParseMode::Lenient).unwrap();
+        assert_eq!(product.circle_id, "RG62982");
+        assert_eq!(
+            product.released_at,
+            NaiveDate::from_ymd_opt(2022, 7, 17).unwrap()
+        );
+
+        let ajax = super::ajax_json(&recorded_body(name, "/product/info/ajax")).unwrap();
+        assert!(ajax.contains_key(&id));
+
+        let review = super::review_json(&recorded_body(name, "/api/review")).unwrap();
+        assert!(review.is_success);
+        assert!(review.review_list.iter().all(|r| r.workno == id));
+    }
+
+    #[test]
+    fn parse_recorded_product_json() {
+        let json = recorded_body(
+            "product_api/get_product_api_1_content",
+            "/api/=/product.json",
+        );
+        let product = super::product_json(&json).unwrap();
+        assert_eq!(product.workno, "RJ403038");
+        assert_eq!(product.maker_name, "Yostar");
+    }
+
+    #[test]
+    fn parse_recorded_search() {
+        let json = recorded_body("search/search_product_1", "/fsr/ajax/=");
+        let result = super::search_ajax(&json).unwrap();
+        assert!(result.products.len() >= 10);
+        assert!(result.count >= result.products.len() as i32);
+
+        let html = recorded_body("circle/get_circle_1", "without_order/1/maker_id/RG24350");
+        let result = super::circle_html(&html).unwrap();
+        assert_eq!(result.products.len(), 50);
+    }
+}
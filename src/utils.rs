@@ -1,17 +1,42 @@
-use crate::error::{DlsiteError, Result};
+use crate::error::{DlsiteError, ParseError, Result};
 
 pub(crate) trait ToParseError<T> {
     fn to_parse_error(self, msg: &str) -> Result<T>;
+    /// Same as `to_parse_error`, but also records the field or selector which failed.
+    fn to_field_error(self, field: &str, msg: &str) -> Result<T>;
 }
 
 impl<T> ToParseError<T> for Option<T> {
     fn to_parse_error(self, msg: &str) -> Result<T> {
-        self.ok_or_else(|| DlsiteError::Parse(msg.to_string()))
+        self.ok_or_else(|| DlsiteError::parse(msg))
+    }
+
+    fn to_field_error(self, field: &str, msg: &str) -> Result<T> {
+        self.ok_or_else(|| ParseError::new(msg).field(field).into())
     }
 }
 
-impl<T, E> ToParseError<T> for std::result::Result<T, E> {
+impl<T, E> ToParseError<T> for std::result::Result<T, E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
     fn to_parse_error(self, msg: &str) -> Result<T> {
-        self.map_err(|_| DlsiteError::Parse(msg.to_string()))
+        self.map_err(|e| ParseError::new(msg).source(e).into())
+    }
+
+    fn to_field_error(self, field: &str, msg: &str) -> Result<T> {
+        self.map_err(|e| ParseError::new(msg).field(field).source(e).into())
     }
 }
+
+/// FNV-1a hash, which is stable across builds unlike `DefaultHasher`.
+pub(crate) fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Japan Standard Time, which DLsite uses for dates.
+pub(crate) fn jst() -> chrono::FixedOffset {
+    chrono::FixedOffset::east_opt(9 * 3600).unwrap()
+}